@@ -0,0 +1,78 @@
+//! Acknowledgements stop firing alarms from being re-notified (and escalated) while someone is
+//! working on them. An acknowledgement lasts until its alarm resolves or it expires.
+
+use crate::pager::{AlarmKind, PagerAlarm};
+use serde::{Deserialize, Serialize};
+use tendermint::{chain, Time};
+
+/// Request to acknowledge the alarms firing on a chain.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AckRequest {
+    /// Chain ID of the alarms to acknowledge.
+    pub chain_id: chain::Id,
+
+    /// Only acknowledge alarms of this kind.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<AlarmKind>,
+
+    /// Only acknowledge alarms about this validator or node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    /// Who is acknowledging the alarms.
+    pub by: String,
+
+    /// Note about the acknowledgement, e.g. what's being done about it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+
+    /// Number of seconds until the acknowledgement expires, defaults to `ack_ttl_secs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+}
+
+impl AckRequest {
+    /// Does the request apply to the given alarm?
+    pub fn matches(&self, alarm: &PagerAlarm) -> bool {
+        self.chain_id == alarm.chain_id
+            && self.kind.is_none_or(|kind| kind == alarm.kind)
+            && self
+                .subject
+                .as_ref()
+                .is_none_or(|subject| subject == &alarm.subject)
+    }
+}
+
+/// Acknowledgement of a firing alarm.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Ack {
+    /// Chain ID of the acknowledged alarm.
+    pub chain_id: chain::Id,
+
+    /// Kind of the acknowledged alarm.
+    pub kind: AlarmKind,
+
+    /// Subject of the acknowledged alarm.
+    pub subject: String,
+
+    /// Who acknowledged the alarm.
+    pub by: String,
+
+    /// Note about the acknowledgement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+
+    /// Time the alarm was acknowledged.
+    pub at: Time,
+
+    /// Time the acknowledgement expires.
+    pub expires: Time,
+}
+
+impl Ack {
+    /// Is the acknowledgement still in effect at the given time?
+    pub fn is_active(&self, now: Time) -> bool {
+        now < self.expires
+    }
+}
@@ -0,0 +1,491 @@
+//! HTTP API exposing the state of each chain monitor as JSON.
+//!
+//! Endpoints:
+//!
+//! - `GET /`: web dashboard (see [`crate::dashboard`])
+//! - `GET /api/events`: server-sent events stream of dashboard updates
+//! - `GET /api/chains`: status of all monitored chains
+//! - `GET /api/chains/{id}`: status of a particular chain
+//! - `GET /api/chains/{id}/blocks[?limit=N]`: recent blocks with their signing status
+//! - `GET /api/chains/{id}/validators[?limit=N]`: every validator ranked by missed blocks
+//! - `GET /api/nodes`: sync status and peers of each of our own nodes
+//! - `GET /api/alarms`: alarms which are currently firing
+//! - `GET /api/silences`: configured and runtime silences
+//! - `POST /api/silences`: add a silence
+//! - `DELETE /api/silences/{id}`: remove a silence added at runtime
+//! - `GET /api/acks`: acknowledgements which haven't expired
+//! - `POST /api/acks`: acknowledge the alarms firing on a chain
+//! - `GET /api/cluster`: this instance's view of its redundant peers and its shared state (see
+//!   [`crate::cluster`])
+//! - `GET /healthz`: liveness check which fails if any chain monitor has stopped running
+//! - `GET /readyz`: readiness check which fails if any chain hasn't imported a block recently
+
+use crate::{
+    ack::AckRequest,
+    cluster::Cluster,
+    config::SilenceConfig,
+    dashboard::{self, DashboardUpdate},
+    health::HealthReport,
+    http,
+    pager::{PagerAlarm, PagerBuffer, PagerRequest, PagerResponse},
+    status::{ChainStatus, StatusBuffer, StatusRequest, StatusResponse},
+};
+use hyper::{
+    body, header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{convert::Infallible, net::SocketAddr};
+use tendermint::chain;
+use tower::{Service, ServiceExt};
+use tracing::{error, info};
+
+/// Header marking requests forwarded from a follower, which are never forwarded again.
+const FORWARDED_HEADER: &str = "X-Observatory-Forwarded";
+
+/// Run the HTTP API server until it fails.
+pub async fn serve(
+    addr: SocketAddr,
+    status_service: StatusBuffer,
+    pager_service: PagerBuffer,
+    cluster: Cluster,
+) {
+    let make_service = make_service_fn(move |_conn| {
+        let api = Api {
+            status_service: status_service.clone(),
+            pager_service: pager_service.clone(),
+            cluster: cluster.clone(),
+        };
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let mut api = api.clone();
+                async move { Ok::<_, Infallible>(api.handle(request).await) }
+            }))
+        }
+    });
+
+    info!("HTTP API listening on {addr}");
+
+    if let Err(err) = Server::bind(&addr).serve(make_service).await {
+        error!("HTTP API server error: {err}");
+    }
+}
+
+/// HTTP API request handler.
+#[derive(Clone)]
+struct Api {
+    /// Status service used to look up chain state.
+    status_service: StatusBuffer,
+
+    /// Pager service used to look up alarms.
+    pager_service: PagerBuffer,
+
+    /// Redundant instances this instance is monitoring.
+    cluster: Cluster,
+}
+
+impl Api {
+    /// Handle an incoming HTTP request.
+    async fn handle(&mut self, request: Request<Body>) -> Response<Body> {
+        let path = request.uri().path().to_owned();
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+
+        // Followers mirror acknowledgements and runtime silences from the leader, so changes to
+        // them are made there
+        if matches!(segments.as_slice(), ["api", "silences" | "acks", ..])
+            && request.method() != Method::GET
+            && !request.headers().contains_key(FORWARDED_HEADER)
+            && let Some(leader_url) = self.cluster.leader_url()
+        {
+            return forward(request, &leader_url).await;
+        }
+
+        match segments.as_slice() {
+            ["api", "silences", rest @ ..] => return self.handle_silences(request, rest).await,
+            ["api", "acks", rest @ ..] => return self.handle_acks(request, rest).await,
+            _ => (),
+        }
+
+        if request.method() != Method::GET {
+            return error_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+
+        match segments.as_slice() {
+            [] => Response::builder()
+                .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+                .body(Body::from(dashboard::INDEX_HTML))
+                .expect("couldn't build HTTP response"),
+            ["api", "events"] => self.events(),
+            ["api", "chains"] => {
+                let chains = self.chains().await;
+                let alarms = self.active_alarms().await;
+                let reports = chains
+                    .iter()
+                    .map(|status| ChainReport::new(status, &alarms))
+                    .collect::<Vec<_>>();
+
+                json_response(&reports)
+            }
+            ["api", "chains", chain_id] => match self.chain(chain_id).await {
+                Some(status) => {
+                    let alarms = self.active_alarms().await;
+                    json_response(&ChainReport::new(&status, &alarms))
+                }
+                None => error_response(StatusCode::NOT_FOUND, "unknown chain"),
+            },
+            ["api", "chains", chain_id, "blocks"] => match self.chain(chain_id).await {
+                Some(status) => {
+                    let limit = query_param(request.uri().query(), "limit")
+                        .and_then(|limit| limit.parse().ok())
+                        .unwrap_or(status.blocks.len());
+
+                    json_response(&status.blocks[..limit.min(status.blocks.len())])
+                }
+                None => error_response(StatusCode::NOT_FOUND, "unknown chain"),
+            },
+            ["api", "chains", chain_id, "validators"] => match self.chain(chain_id).await {
+                Some(ChainStatus {
+                    signing: Some(snapshot),
+                    ..
+                }) => {
+                    let mut report = snapshot.report();
+
+                    if let Some(limit) = query_param(request.uri().query(), "limit")
+                        .and_then(|limit| limit.parse().ok())
+                    {
+                        report.validators.truncate(limit);
+                    }
+
+                    json_response(&report)
+                }
+                Some(_) => error_response(StatusCode::NOT_FOUND, "validator set not yet known"),
+                None => error_response(StatusCode::NOT_FOUND, "unknown chain"),
+            },
+            ["api", "nodes"] => match self.status(StatusRequest::GetNodes).await {
+                StatusResponse::Nodes(nodes) => json_response(&nodes),
+                other => panic!("unexpected StatusService response: {:?}", other),
+            },
+            ["api", "alarms"] => json_response(&self.active_alarms().await),
+            ["api", "cluster"] => json_response(&self.cluster.status()),
+            ["healthz"] => {
+                let health = self.health().await;
+                health_response(health.live, &health)
+            }
+            ["readyz"] => {
+                let health = self.health().await;
+                health_response(health.ready, &health)
+            }
+            _ => error_response(StatusCode::NOT_FOUND, "not found"),
+        }
+    }
+
+    /// Handle a request to list, add or remove silences.
+    async fn handle_silences(&mut self, request: Request<Body>, path: &[&str]) -> Response<Body> {
+        match (request.method(), path) {
+            (&Method::GET, []) => match self.pager(PagerRequest::GetSilences).await {
+                PagerResponse::Silences(silences) => json_response(&silences),
+                other => panic!("unexpected PagerService response: {:?}", other),
+            },
+            (&Method::POST, []) => {
+                let config = match parse_body::<SilenceConfig>(request, "silence").await {
+                    Ok(config) => config,
+                    Err(response) => return response,
+                };
+
+                match self.pager(PagerRequest::AddSilence(config)).await {
+                    PagerResponse::AddedSilence(Ok(silence)) => {
+                        let mut response = json_response(&silence);
+                        *response.status_mut() = StatusCode::CREATED;
+                        response
+                    }
+                    PagerResponse::AddedSilence(Err(err)) => {
+                        error_response(StatusCode::BAD_REQUEST, &err.to_string())
+                    }
+                    other => panic!("unexpected PagerService response: {:?}", other),
+                }
+            }
+            (&Method::DELETE, [id]) => {
+                match self
+                    .pager(PagerRequest::RemoveSilence(id.to_string()))
+                    .await
+                {
+                    PagerResponse::RemovedSilence(Ok(Some(silence))) => json_response(&silence),
+                    PagerResponse::RemovedSilence(Ok(None)) => {
+                        error_response(StatusCode::NOT_FOUND, "unknown silence")
+                    }
+                    PagerResponse::RemovedSilence(Err(err)) => {
+                        error_response(StatusCode::BAD_REQUEST, &err.to_string())
+                    }
+                    other => panic!("unexpected PagerService response: {:?}", other),
+                }
+            }
+            (_, [] | [_]) => error_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"),
+            _ => error_response(StatusCode::NOT_FOUND, "not found"),
+        }
+    }
+
+    /// Handle a request to list acknowledgements or acknowledge alarms.
+    async fn handle_acks(&mut self, request: Request<Body>, path: &[&str]) -> Response<Body> {
+        match (request.method(), path) {
+            (&Method::GET, []) => match self.pager(PagerRequest::GetAcks).await {
+                PagerResponse::Acks(acks) => json_response(&acks),
+                other => panic!("unexpected PagerService response: {:?}", other),
+            },
+            (&Method::POST, []) => {
+                let ack_request = match parse_body::<AckRequest>(request, "acknowledgement").await {
+                    Ok(ack_request) => ack_request,
+                    Err(response) => return response,
+                };
+
+                match self.pager(PagerRequest::Ack(ack_request)).await {
+                    PagerResponse::Acked(Ok(acks)) if acks.is_empty() => {
+                        error_response(StatusCode::NOT_FOUND, "no matching alarms are firing")
+                    }
+                    PagerResponse::Acked(Ok(acks)) => {
+                        let mut response = json_response(&acks);
+                        *response.status_mut() = StatusCode::CREATED;
+                        response
+                    }
+                    PagerResponse::Acked(Err(err)) => {
+                        error_response(StatusCode::BAD_REQUEST, &err.to_string())
+                    }
+                    other => panic!("unexpected PagerService response: {:?}", other),
+                }
+            }
+            (_, []) => error_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"),
+            _ => error_response(StatusCode::NOT_FOUND, "not found"),
+        }
+    }
+
+    /// Stream dashboard updates as server-sent events until the client disconnects.
+    fn events(&self) -> Response<Body> {
+        let (mut sender, body) = Body::channel();
+        let mut api = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let chains = api.chains().await;
+                let alarms = api.active_alarms().await;
+                let event = DashboardUpdate::new(&chains, &alarms).to_event();
+
+                if sender.send_data(event.into()).await.is_err() {
+                    break;
+                }
+
+                tokio::time::sleep(dashboard::UPDATE_INTERVAL).await;
+            }
+        });
+
+        Response::builder()
+            .header(header::CONTENT_TYPE, "text/event-stream")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(body)
+            .expect("couldn't build HTTP response")
+    }
+
+    /// Get the status of all chains.
+    async fn chains(&mut self) -> Vec<ChainStatus> {
+        match self.status(StatusRequest::GetChains).await {
+            StatusResponse::Chains(chains) => chains,
+            other => panic!("unexpected StatusService response: {:?}", other),
+        }
+    }
+
+    /// Get the status of a particular chain.
+    async fn chain(&mut self, chain_id: &str) -> Option<ChainStatus> {
+        let chain_id = chain::Id::try_from(chain_id).ok()?;
+
+        match self.status(StatusRequest::GetChain(chain_id)).await {
+            StatusResponse::Chain(chain) => chain.map(|chain| *chain),
+            other => panic!("unexpected StatusService response: {:?}", other),
+        }
+    }
+
+    /// Get the health of all chain monitors.
+    async fn health(&mut self) -> HealthReport {
+        match self.status(StatusRequest::GetHealth).await {
+            StatusResponse::Health(health) => health,
+            other => panic!("unexpected StatusService response: {:?}", other),
+        }
+    }
+
+    /// Send a request to the status service.
+    async fn status(&mut self, request: StatusRequest) -> StatusResponse {
+        self.status_service
+            .ready()
+            .await
+            .expect("StatusService not ready")
+            .call(request)
+            .await
+            .expect("StatusService error")
+    }
+
+    /// Get alarms which are currently firing.
+    async fn active_alarms(&mut self) -> Vec<PagerAlarm> {
+        match self.pager(PagerRequest::ActiveAlarms).await {
+            PagerResponse::ActiveAlarms(alarms) => alarms,
+            other => panic!("unexpected PagerService response: {:?}", other),
+        }
+    }
+
+    /// Send a request to the pager service.
+    async fn pager(&mut self, request: PagerRequest) -> PagerResponse {
+        self.pager_service
+            .ready()
+            .await
+            .expect("PagerService not ready")
+            .call(request)
+            .await
+            .expect("PagerService error")
+    }
+}
+
+/// Chain status along with the alarms firing for that chain.
+#[derive(Serialize)]
+struct ChainReport<'a> {
+    #[serde(flatten)]
+    status: &'a ChainStatus,
+
+    alarms: Vec<&'a PagerAlarm>,
+}
+
+impl<'a> ChainReport<'a> {
+    fn new(status: &'a ChainStatus, alarms: &'a [PagerAlarm]) -> Self {
+        Self {
+            status,
+            alarms: alarms
+                .iter()
+                .filter(|alarm| alarm.chain_id == status.chain_id)
+                .collect(),
+        }
+    }
+}
+
+/// Find the value of the given parameter in a URL query string.
+fn query_param<'a>(query: Option<&'a str>, name: &str) -> Option<&'a str> {
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Forward a request to the leader, passing its response through. Only failures to reach the
+/// leader are reported as a bad gateway.
+async fn forward(request: Request<Body>, leader_url: &str) -> Response<Body> {
+    let method = request.method().clone();
+    let url = format!("{leader_url}{}", request.uri().path());
+
+    let bytes = match body::to_bytes(request.into_body()).await {
+        Ok(bytes) => bytes,
+        Err(err) => return error_response(StatusCode::BAD_REQUEST, &err.to_string()),
+    };
+    let body = (!bytes.is_empty()).then(|| String::from_utf8_lossy(&bytes).into_owned());
+
+    match http::send(method, &url, &[(FORWARDED_HEADER, "1")], body).await {
+        Ok((status, bytes)) => Response::builder()
+            .status(status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(bytes))
+            .expect("couldn't build HTTP response"),
+        Err(err) => error_response(
+            StatusCode::BAD_GATEWAY,
+            &format!("couldn't forward request to the leader at {leader_url}: {err}"),
+        ),
+    }
+}
+
+/// Parse a JSON request body, or build an error response describing why it's invalid.
+async fn parse_body<T: DeserializeOwned>(
+    request: Request<Body>,
+    what: &str,
+) -> Result<T, Response<Body>> {
+    let bytes = body::to_bytes(request.into_body())
+        .await
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, &err.to_string()))?;
+
+    serde_json::from_slice(&bytes)
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, &format!("invalid {what}: {err}")))
+}
+
+/// Serialize the given value as a JSON response.
+fn json_response<T: Serialize + ?Sized>(value: &T) -> Response<Body> {
+    let body = serde_json::to_string(value).expect("couldn't serialize JSON response");
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("couldn't build HTTP response")
+}
+
+/// Build a health check response which is only successful if `healthy` is true.
+fn health_response(healthy: bool, health: &HealthReport) -> Response<Body> {
+    let mut response = json_response(health);
+
+    if !healthy {
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    }
+
+    response
+}
+
+/// Build a JSON error response.
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    let mut response = json_response(&serde_json::json!({ "error": message }));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{error_response, forward, query_param};
+    use hyper::{
+        body,
+        service::{make_service_fn, service_fn},
+        Body, Method, Request, Server, StatusCode,
+    };
+    use std::convert::Infallible;
+
+    #[tokio::test]
+    async fn forwards_leader_responses() {
+        // A leader which rejects every request as a validation error
+        let leader =
+            Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service_fn(|_conn| async {
+                Ok::<_, Infallible>(service_fn(|_request| async {
+                    Ok::<_, Infallible>(error_response(StatusCode::NOT_FOUND, "no matching alarms"))
+                }))
+            }));
+        let leader_url = format!("http://{}", leader.local_addr());
+        tokio::spawn(leader);
+
+        let request = || {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/api/acks")
+                .body(Body::from("{}"))
+                .unwrap()
+        };
+
+        let response = forward(request(), &leader_url).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let bytes = body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&bytes[..], br#"{"error":"no matching alarms"}"#);
+
+        // Only failing to reach the leader is a bad gateway
+        let response = forward(request(), "http://127.0.0.1:1").await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[test]
+    fn parses_query_params() {
+        assert_eq!(query_param(Some("limit=10"), "limit"), Some("10"));
+        assert_eq!(query_param(Some("a=1&limit=5"), "limit"), Some("5"));
+        assert_eq!(query_param(Some("a=1"), "limit"), None);
+        assert_eq!(query_param(None, "limit"), None);
+    }
+}
@@ -82,7 +82,9 @@ impl Application for ObservatoryApp {
 
     /// Get tracing configuration from command-line options
     fn tracing_config(&self, command: &EntryPoint) -> trace::Config {
-        if command.verbose {
+        if let Some(filter) = command.log_filter() {
+            filter.to_owned().into()
+        } else if command.verbose {
             trace::Config::verbose()
         } else {
             trace::Config::default()
@@ -1,10 +1,20 @@
-use crate::{chain_state::ChainState, client_manager::ClientManager};
-use std::time::Duration;
-use tendermint::{account, block, chain, Time};
+use crate::{
+    chain_state::{ChainState, Misbehavior, Vote},
+    client_manager::ClientManager,
+    config::ValidatorConfig,
+    error::{Error, ErrorKind},
+    health::Heartbeat,
+    proposer::round_proposers,
+    signing_report::{SigningReport, SigningSnapshot},
+    status::{BlockStatus, ChainStatus, ValidatorStatus},
+};
+use futures::StreamExt;
+use std::{collections::BTreeMap as Map, sync::Arc, time::Duration};
+use tendermint::{account, block, chain, validator, Time};
 use tendermint_rpc::{
     endpoint::block::Response as BlockResponse,
     error::{Error as RpcError, ErrorDetail as RpcErrorDetail},
-    Client as _,
+    Client as _, Paging,
 };
 use tokio::time::sleep;
 use tracing::{info, trace, warn};
@@ -23,17 +33,49 @@ pub struct ChainMonitor {
 
     /// Offset between the last wall time and the last block time
     bft_time_delta: Duration,
+
+    /// Time the latest block was imported.
+    imported_at: Time,
+
+    /// Heartbeat updated every time the chain is polled.
+    heartbeat: Heartbeat,
+
+    /// Most recently fetched validator set.
+    validator_set: Arc<[validator::Info]>,
+
+    /// Voting power of each validator as of roughly one history window ago, used to detect
+    /// significant changes.
+    reference_powers: Option<Map<account::Id, u64>>,
+
+    /// Validators which were scheduled to propose in rounds that didn't produce a block, by height.
+    skipped_proposers: Map<block::Height, Vec<account::Id>>,
 }
 
 impl ChainMonitor {
+    /// Number of blocks to fetch concurrently when backfilling.
+    const BACKFILL_CONCURRENCY: usize = 10;
+
+    /// Interval in blocks at which the validator set is refreshed.
+    const VALIDATOR_SET_INTERVAL: u64 = 10;
+
     /// Create a new chain monitor from an RPC client manager.
-    // TODO(tarcieri): error handling
-    pub async fn new(chain_id: chain::Id, client_manager: ClientManager) -> Self {
+    ///
+    /// Returns an error if an RPC endpoint serves a different chain.
+    pub async fn new(
+        chain_id: chain::Id,
+        client_manager: ClientManager,
+        heartbeat: Heartbeat,
+    ) -> Result<Self, Error> {
         let mut chain_monitor = Self {
             chain_state: ChainState::new(chain_id),
             client_manager,
             block_height: block::Height::default(),
             bft_time_delta: Duration::ZERO,
+            imported_at: Time::now(),
+            heartbeat,
+            validator_set: Arc::new([]),
+            reference_powers: None,
+            skipped_proposers: Map::new(),
         };
 
         let responses = chain_monitor
@@ -48,11 +90,12 @@ impl ChainMonitor {
             let chain_id = &response.block.header.chain_id;
 
             if chain_id != chain_monitor.chain_id() {
-                // TODO(tarcieri): don't panic
-                panic!(
-                    "unexpected chain ID '{chain_id}'! (expecting {})",
-                    chain_monitor.chain_id()
-                );
+                return Err(ErrorKind::Config
+                    .context(format!(
+                        "unexpected chain ID '{chain_id}'! (expecting {})",
+                        chain_monitor.chain_id()
+                    ))
+                    .into());
             }
 
             if block_height > chain_monitor.block_height {
@@ -68,19 +111,28 @@ impl ChainMonitor {
             }
         }
 
+        chain_monitor.refresh_validator_set().await;
+        chain_monitor.reference_powers = chain_monitor.voting_powers();
+
         info!(
             "[{}] initialized at height {}",
             chain_monitor.chain_id(),
             block_height_with_commas(chain_monitor.block_height)
         );
 
-        chain_monitor
+        Ok(chain_monitor)
     }
 
     /// Run the chain monitor.
     pub async fn fetch_next_block(&mut self) {
-        if u64::from(self.block_height) % self.chain_state.history_size() as u64 == 0 {
+        let height = u64::from(self.block_height);
+
+        if height % self.chain_state.history_size() as u64 == 0 {
             self.check_latest_blocks().await;
+            self.reference_powers = self.voting_powers();
+            self.refresh_validator_set().await;
+        } else if height % Self::VALIDATOR_SET_INTERVAL == 0 {
+            self.refresh_validator_set().await;
         }
 
         let started_at = Time::now();
@@ -104,6 +156,7 @@ impl ChainMonitor {
             );
 
             sleep(sleep_duration).await;
+            self.heartbeat.beat();
 
             let responses = self
                 .client_manager
@@ -126,6 +179,7 @@ impl ChainMonitor {
                             added_block = true;
                             self.block_height = next_height;
                             self.bft_time_delta = bft_time_delta;
+                            self.imported_at = now;
 
                             let duration = now.duration_since(started_at).unwrap_or(Duration::ZERO);
 
@@ -152,6 +206,165 @@ impl ChainMonitor {
                 break;
             }
         }
+
+        self.scan_skipped_rounds().await;
+    }
+
+    /// Fetch blocks preceding the latest known one until the given number of blocks are known.
+    pub async fn backfill(&mut self, block_count: usize) {
+        self.chain_state.set_history_size(block_count);
+
+        let latest_height = u64::from(self.block_height);
+        let oldest_height = latest_height
+            .saturating_sub((block_count as u64).saturating_sub(1))
+            .max(1);
+        let known_blocks = self.chain_state.blocks().count() as u64;
+        let heights = (oldest_height..=latest_height.saturating_sub(known_blocks)).rev();
+
+        let responses = futures::stream::iter(heights)
+            .map(|height| self.fetch_block(height))
+            .buffered(Self::BACKFILL_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        // Stop at the first gap so the history remains contiguous
+        for response in responses.into_iter().map_while(|response| response) {
+            self.chain_state
+                .import_historical_block(response.block_id, response.block);
+        }
+
+        self.scan_skipped_rounds().await;
+    }
+
+    /// Determine which validators were scheduled to propose in rounds which failed to produce a
+    /// block, for every known height which was committed in a round other than 0.
+    async fn scan_skipped_rounds(&mut self) {
+        if let Some(oldest_height) = self.chain_state.oldest_height() {
+            self.skipped_proposers = self.skipped_proposers.split_off(&oldest_height);
+        }
+
+        let skipped_rounds = self
+            .chain_state
+            .skipped_rounds()
+            .filter(|(height, _)| !self.skipped_proposers.contains_key(height))
+            .collect::<Vec<_>>();
+
+        for (height, round) in skipped_rounds {
+            // The round 0 proposer is found by replaying the previous height's proposer
+            // priorities, so both validator sets are needed (there's none before the first block)
+            let previous = match height.value().checked_sub(1).filter(|height| *height > 0) {
+                Some(previous_height) => self.fetch_validators(previous_height).await,
+                None => Some(vec![]),
+            };
+            let current = self.fetch_validators(height.value()).await;
+
+            // Leave the height out on failure so it's retried with the next block
+            let (Some(previous), Some(current)) = (previous, current) else {
+                warn!(
+                    "[{}] couldn't fetch validator sets at height {}",
+                    self.chain_id(),
+                    height
+                );
+                continue;
+            };
+
+            let proposers = round_proposers(&previous, &current, round.value());
+
+            info!(
+                "[{}] block {} was committed in round {}",
+                self.chain_id(),
+                block_height_with_commas(height),
+                round
+            );
+
+            self.skipped_proposers.insert(height, proposers);
+        }
+    }
+
+    /// Fetch the validator set at the given height from any RPC endpoint.
+    async fn fetch_validators(&self, height: u64) -> Option<Vec<validator::Info>> {
+        let height = block::Height::try_from(height).ok()?;
+
+        self.client_manager
+            .request(|client| client.validators(height, Paging::All))
+            .await
+            .into_iter()
+            .find_map(|result| result.ok())
+            .map(|response| response.validators)
+    }
+
+    /// Fetch the validator set at the latest known height.
+    pub async fn refresh_validator_set(&mut self) {
+        let height = self.block_height;
+
+        let response = self
+            .client_manager
+            .request(|client| client.validators(height, Paging::All))
+            .await
+            .into_iter()
+            .find_map(|result| result.ok());
+
+        match response {
+            Some(response) => self.validator_set = response.validators.into(),
+            None => warn!(
+                "[{}] couldn't fetch validator set at height {}",
+                self.chain_id(),
+                height
+            ),
+        }
+    }
+
+    /// Get the voting power of every member of the validator set, if it's known.
+    fn voting_powers(&self) -> Option<Map<account::Id, u64>> {
+        if self.validator_set.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.validator_set
+                .iter()
+                .map(|info| (info.address, info.power()))
+                .collect(),
+        )
+    }
+
+    /// Get the current voting power of the given consensus key ID, or `0` if it isn't in the
+    /// validator set.
+    ///
+    /// Returns `None` if the validator set isn't known.
+    pub fn voting_power(&self, validator_address: account::Id) -> Option<u64> {
+        self.voting_powers()
+            .map(|powers| powers.get(&validator_address).copied().unwrap_or(0))
+    }
+
+    /// Get the voting power of the given consensus key ID as of roughly one history window ago.
+    pub fn reference_voting_power(&self, validator_address: account::Id) -> Option<u64> {
+        self.reference_powers
+            .as_ref()
+            .map(|powers| powers.get(&validator_address).copied().unwrap_or(0))
+    }
+
+    /// Fetch the block at the given height from the first RPC endpoint which has it.
+    async fn fetch_block(&self, height: u64) -> Option<BlockResponse> {
+        let height = block::Height::try_from(height).ok()?;
+
+        let response = self
+            .client_manager
+            .request(|client| client.block(height))
+            .await
+            .into_iter()
+            .find_map(|result| result.ok());
+
+        if response.is_none() {
+            warn!("[{}] couldn't fetch block {}", self.chain_id(), height);
+        }
+
+        response
+    }
+
+    /// Get the latest imported block height.
+    pub fn height(&self) -> block::Height {
+        self.block_height
     }
 
     /// Get the chain ID being monitored.
@@ -164,11 +377,139 @@ impl ChainMonitor {
         self.chain_state.missed_blocks(validator_address)
     }
 
+    /// Get the count of blocks proposed by the given consensus key ID.
+    pub fn proposed_blocks(&self, validator_address: account::Id) -> usize {
+        self.chain_state.proposed_blocks(validator_address)
+    }
+
+    /// Get the count of rounds where the given consensus key ID was scheduled to propose but
+    /// the round advanced without its block.
+    pub fn missed_proposals(&self, validator_address: account::Id) -> usize {
+        self.skipped_proposers
+            .values()
+            .filter(|proposers| proposers.contains(&validator_address))
+            .count()
+    }
+
+    /// Get the height at which the given consensus key ID missed its latest scheduled proposal,
+    /// unless it has since proposed a block.
+    pub fn last_missed_proposal(&self, validator_address: account::Id) -> Option<block::Height> {
+        let missed = self
+            .skipped_proposers
+            .iter()
+            .rev()
+            .find(|(_, proposers)| proposers.contains(&validator_address))
+            .map(|(height, _)| *height)?;
+
+        match self.chain_state.last_proposed(validator_address) {
+            Some(proposed) if proposed >= missed => None,
+            _ => Some(missed),
+        }
+    }
+
+    /// Get the moniker of the given validator, if the RPC endpoint reports one.
+    pub fn moniker(&self, validator_address: account::Id) -> Option<&str> {
+        self.validator_set
+            .iter()
+            .find(|info| info.address == validator_address)
+            .and_then(|info| info.name.as_deref())
+    }
+
+    /// Get evidence of misbehavior included in blocks in the history window.
+    pub fn misbehavior(&self) -> Vec<Misbehavior> {
+        self.chain_state.misbehavior()
+    }
+
+    /// Get the number of blocks in the history window which have a last commit.
+    pub fn commit_count(&self) -> usize {
+        self.chain_state.commit_count()
+    }
+
+    /// Get the count of nil votes for the given consensus key ID.
+    pub fn nil_votes(&self, validator_address: account::Id) -> usize {
+        self.chain_state.nil_votes(validator_address)
+    }
+
     /// Get the count of recent blocks for the given consensus key ID.
     pub fn recent_blocks(&self, validator_address: account::Id) -> usize {
         self.chain_state.recent_blocks(validator_address)
     }
 
+    /// Compute signing stats for the whole validator set, if it's known.
+    pub fn signing_report(&self, validators: &[ValidatorConfig]) -> Option<SigningReport> {
+        self.signing_snapshot(validators)
+            .map(|snapshot| snapshot.report())
+    }
+
+    /// Take a snapshot from which signing stats for the whole validator set can be computed, if
+    /// it's known.
+    pub fn signing_snapshot(&self, validators: &[ValidatorConfig]) -> Option<SigningSnapshot> {
+        if self.validator_set.is_empty() {
+            return None;
+        }
+
+        Some(SigningSnapshot::new(
+            &self.chain_state,
+            self.block_height,
+            self.validator_set.clone(),
+            validators,
+        ))
+    }
+
+    /// Take a snapshot of the current state of this monitor for the given validators.
+    pub fn status(&self, validators: &[ValidatorConfig]) -> ChainStatus {
+        let blocks = self
+            .chain_state
+            .blocks()
+            .map(|data| BlockStatus {
+                height: data.height(),
+                hash: data.id().hash,
+                time: data.time(),
+                votes: validators
+                    .iter()
+                    .map(|validator| data.vote_by(validator.addr))
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        let validator_statuses = validators
+            .iter()
+            .enumerate()
+            .map(|(index, validator)| {
+                let proposed_blocks = self.proposed_blocks(validator.addr);
+                let missed_proposals = self.missed_proposals(validator.addr);
+                let scheduled_proposals = proposed_blocks + missed_proposals;
+
+                ValidatorStatus {
+                    name: validator.name.clone(),
+                    addr: validator.addr,
+                    missed_blocks: self.missed_blocks(validator.addr),
+                    recent_blocks: self.recent_blocks(validator.addr),
+                    nil_votes: self.nil_votes(validator.addr),
+                    uptime: uptime(blocks.iter().map(|block| block.votes[index])),
+                    proposed_blocks,
+                    missed_proposals,
+                    voting_power: self.voting_power(validator.addr),
+                    proposal_success_rate: (scheduled_proposals > 0)
+                        .then(|| proposed_blocks as f64 * 100.0 / scheduled_proposals as f64),
+                }
+            })
+            .collect();
+
+        ChainStatus {
+            chain_id: self.chain_id().clone(),
+            height: self.block_height,
+            consensus_time_ms: self.chain_state.consensus_time().as_millis() as u64,
+            validators: validator_statuses,
+            rpc_endpoints: self.client_manager.endpoint_statuses(),
+            imported_at: self.imported_at,
+            updated_at: Time::now(),
+            blocks,
+            signing: self.signing_snapshot(validators),
+            consensus: None,
+        }
+    }
+
     /// Fetch the latest blocks for the given chain.
     async fn fetch_latest_blocks(&self) -> Vec<Result<BlockResponse, RpcError>> {
         self.client_manager
@@ -241,6 +582,19 @@ impl ChainMonitor {
     }
 }
 
+/// Percentage of blocks with a last commit which were signed.
+fn uptime(votes: impl Iterator<Item = Option<Vote>>) -> Option<f64> {
+    let (total, signed) = votes.flatten().fold((0, 0), |(total, count), vote| {
+        (total + 1, count + usize::from(vote.is_signed()))
+    });
+
+    if total == 0 {
+        return None;
+    }
+
+    Some(signed as f64 * 100.0 / total as f64)
+}
+
 /// Helper function to format block heights with commas
 fn block_height_with_commas(height: block::Height) -> String {
     height
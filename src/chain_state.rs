@@ -1,8 +1,16 @@
-use std::{collections::VecDeque, time::Duration};
-use tendermint::{account, block, chain, Block, Time};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap as Map, VecDeque},
+    fmt,
+    sync::Arc,
+    time::Duration,
+};
+use tendermint::{account, block, block::CommitSig, chain, evidence::Evidence, Block, Time};
 
 /// Chain state tracker.
-#[derive(Debug)]
+///
+/// Blocks are shared between clones, so snapshots of the chain state are cheap.
+#[derive(Clone, Debug)]
 pub struct ChainState {
     chain_id: chain::Id,
     blocks: VecDeque<BlockData>,
@@ -35,6 +43,12 @@ impl ChainState {
         self.history_size
     }
 
+    /// Set the number of blocks to retain, discarding the oldest blocks if necessary.
+    pub fn set_history_size(&mut self, history_size: usize) {
+        self.history_size = history_size;
+        self.blocks.truncate(history_size);
+    }
+
     /// Clear the current chain state, discarding all known blocks.
     pub fn clear(&mut self) {
         self.blocks.clear();
@@ -43,7 +57,10 @@ impl ChainState {
     /// Import a block into the chain state.
     pub fn import_block(&mut self, id: block::Id, block: Block) -> bool {
         let mut new_block = false;
-        let block_data = BlockData { id, block };
+        let block_data = BlockData {
+            id,
+            block: Arc::new(block),
+        };
 
         // TODO(tarcieri): make sure blocks are ordered in sequence
         if self.blocks.front().map(|entry| entry.id()) != Some(id) {
@@ -58,11 +75,31 @@ impl ChainState {
         new_block
     }
 
+    /// Import a block which is older than all known blocks into the chain state.
+    ///
+    /// Returns `false` if the history is already full.
+    pub fn import_historical_block(&mut self, id: block::Id, block: Block) -> bool {
+        if self.blocks.len() >= self.history_size {
+            return false;
+        }
+
+        self.blocks.push_back(BlockData {
+            id,
+            block: Arc::new(block),
+        });
+        true
+    }
+
     /// Get the latest block if available.
     pub fn latest_block(&self) -> Option<&BlockData> {
         self.blocks.front()
     }
 
+    /// Iterate over the known blocks, starting with the most recent.
+    pub fn blocks(&self) -> impl Iterator<Item = &BlockData> {
+        self.blocks.iter()
+    }
+
     /// Get estimated consensus time (i.e. average block production rate).
     pub fn consensus_time(&self) -> Duration {
         let mut consensus_times = Vec::with_capacity(self.blocks.len());
@@ -103,10 +140,10 @@ impl ChainState {
         let mut result = 0;
 
         for data in &self.blocks {
-            if let Some(commit) = &data.block.last_commit {
-                if !has_sig(commit, validator_address) {
-                    result += 1;
-                }
+            if let Some(commit) = &data.block.last_commit
+                && !has_sig(commit, validator_address)
+            {
+                result += 1;
             }
         }
 
@@ -129,13 +166,122 @@ impl ChainState {
 
         result
     }
+
+    /// Count the number of blocks where the given consensus key precommitted nil.
+    pub fn nil_votes(&self, validator_address: account::Id) -> usize {
+        self.blocks
+            .iter()
+            .filter(|data| data.vote_by(validator_address) == Some(Vote::Nil))
+            .count()
+    }
+
+    /// Count the number of blocks proposed by the given consensus key.
+    pub fn proposed_blocks(&self, validator_address: account::Id) -> usize {
+        self.blocks
+            .iter()
+            .filter(|data| data.proposer() == validator_address)
+            .count()
+    }
+
+    /// Get the height of the latest block proposed by the given consensus key.
+    pub fn last_proposed(&self, validator_address: account::Id) -> Option<block::Height> {
+        self.blocks
+            .iter()
+            .find(|data| data.proposer() == validator_address)
+            .map(BlockData::height)
+    }
+
+    /// Get the height of the oldest known block.
+    pub fn oldest_height(&self) -> Option<block::Height> {
+        self.blocks.back().map(BlockData::height)
+    }
+
+    /// Iterate over heights which were committed in a round other than 0, along with that round.
+    ///
+    /// This is determined from the last commit of the following block, so the latest block is
+    /// never included.
+    pub fn skipped_rounds(&self) -> impl Iterator<Item = (block::Height, block::Round)> + '_ {
+        self.blocks
+            .iter()
+            .filter_map(|data| data.block.last_commit.as_ref())
+            .filter(|commit| commit.round.value() > 0)
+            .map(|commit| (commit.height, commit.round))
+    }
+
+    /// Get evidence of misbehavior included in the known blocks, starting with the most recent.
+    pub fn misbehavior(&self) -> Vec<Misbehavior> {
+        let mut result = vec![];
+
+        for data in &self.blocks {
+            for evidence in data.block.evidence.iter() {
+                match evidence {
+                    Evidence::DuplicateVote(evidence) => result.push(Misbehavior {
+                        kind: MisbehaviorKind::DuplicateVote,
+                        validator: evidence.vote_a.validator_address,
+                        height: evidence.vote_a.height,
+                        included_at: data.height(),
+                    }),
+                    Evidence::LightClientAttack(evidence) => {
+                        result.extend(evidence.byzantine_validators.iter().map(|info| {
+                            Misbehavior {
+                                kind: MisbehaviorKind::LightClientAttack,
+                                validator: info.address,
+                                height: evidence.common_height,
+                                included_at: data.height(),
+                            }
+                        }))
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Count the number of blocks which have a last commit.
+    pub fn commit_count(&self) -> usize {
+        self.blocks
+            .iter()
+            .filter(|data| data.block.last_commit.is_some())
+            .count()
+    }
+
+    /// Count the votes cast by every validator which appears in a last commit, and the blocks
+    /// proposed by each.
+    pub fn vote_counts(&self) -> Map<account::Id, VoteCounts> {
+        let mut counts = Map::<account::Id, VoteCounts>::new();
+
+        for data in &self.blocks {
+            counts.entry(data.proposer()).or_default().proposed += 1;
+        }
+
+        for commit in self
+            .blocks
+            .iter()
+            .filter_map(|data| data.block.last_commit.as_ref())
+        {
+            for sig in &commit.signatures {
+                match sig {
+                    CommitSig::BlockIdFlagCommit {
+                        validator_address, ..
+                    } => counts.entry(*validator_address).or_default().commit += 1,
+                    CommitSig::BlockIdFlagNil {
+                        validator_address, ..
+                    } => counts.entry(*validator_address).or_default().nil += 1,
+                    CommitSig::BlockIdFlagAbsent => (),
+                }
+            }
+        }
+
+        counts
+    }
 }
 
 /// Data about a particular block in the chain.
 #[derive(Clone, Debug)]
 pub struct BlockData {
     id: block::Id,
-    block: Block,
+    block: Arc<Block>,
 }
 
 impl BlockData {
@@ -144,17 +290,133 @@ impl BlockData {
         self.id
     }
 
+    /// Get the height of this block.
+    pub fn height(&self) -> block::Height {
+        self.block.header.height
+    }
+
     /// Get the block time.
     pub fn time(&self) -> Time {
         self.block.header.time
     }
+
+    /// Get the address of the validator which proposed this block.
+    pub fn proposer(&self) -> account::Id {
+        self.block.header.proposer_address
+    }
+
+    /// Get the vote cast by the given validator in this block's last commit.
+    ///
+    /// Returns `None` if the block has no last commit (e.g. the genesis block).
+    pub fn vote_by(&self, validator_address: account::Id) -> Option<Vote> {
+        self.block
+            .last_commit
+            .as_ref()
+            .map(|commit| vote(commit, validator_address))
+    }
+}
+
+/// Misbehavior by a validator, as proven by evidence included in a block.
+#[derive(Clone, Debug)]
+pub struct Misbehavior {
+    /// Kind of evidence.
+    pub kind: MisbehaviorKind,
+
+    /// Validator which misbehaved.
+    pub validator: account::Id,
+
+    /// Height at which the misbehavior occurred.
+    pub height: block::Height,
+
+    /// Height of the block which included the evidence.
+    pub included_at: block::Height,
+}
+
+impl fmt::Display for Misbehavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at height {} (evidence included at height {})",
+            self.kind, self.height, self.included_at
+        )
+    }
+}
+
+/// Kinds of evidence of misbehavior.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MisbehaviorKind {
+    /// Validator signed conflicting votes, i.e. double signed.
+    DuplicateVote,
+
+    /// Validator took part in an attack on light clients.
+    LightClientAttack,
+}
+
+impl fmt::Display for MisbehaviorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MisbehaviorKind::DuplicateVote => "duplicate vote",
+            MisbehaviorKind::LightClientAttack => "light client attack",
+        })
+    }
+}
+
+/// Vote cast by a validator in a block's last commit.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Vote {
+    /// Precommit for the block.
+    Commit,
+
+    /// Precommit for nil, i.e. the validator was lagging or disagreed with the proposal.
+    Nil,
+
+    /// No precommit was included from the validator.
+    Absent,
+}
+
+impl Vote {
+    /// Was a precommit included from the validator?
+    ///
+    /// Like the Cosmos SDK's slashing module, nil votes count as signed.
+    pub fn is_signed(self) -> bool {
+        self != Vote::Absent
+    }
+}
+
+/// Number of each type of vote cast by a validator, and of blocks it proposed.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct VoteCounts {
+    /// Number of precommits for a block.
+    pub commit: usize,
+
+    /// Number of precommits for nil.
+    pub nil: usize,
+
+    /// Number of blocks proposed.
+    pub proposed: usize,
+}
+
+/// Get the vote cast by the given validator in the given commit.
+fn vote(commit: &block::Commit, validator_address: account::Id) -> Vote {
+    commit
+        .signatures
+        .iter()
+        .find_map(|sig| match sig {
+            CommitSig::BlockIdFlagCommit {
+                validator_address: addr,
+                ..
+            } if *addr == validator_address => Some(Vote::Commit),
+            CommitSig::BlockIdFlagNil {
+                validator_address: addr,
+                ..
+            } if *addr == validator_address => Some(Vote::Nil),
+            _ => None,
+        })
+        .unwrap_or(Vote::Absent)
 }
 
 /// Check if the given commit has a signature for the given validator.
 fn has_sig(commit: &block::Commit, validator_address: account::Id) -> bool {
-    commit.signatures.iter().any(|sig| {
-        sig.validator_address()
-            .map(|addr| addr == validator_address)
-            .unwrap_or(false)
-    })
+    vote(commit, validator_address).is_signed()
 }
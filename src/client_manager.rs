@@ -1,7 +1,12 @@
 use crate::Url;
 use futures::future::{join_all, Future};
-use std::{collections::BTreeMap as Map, time::Duration};
-use tendermint_rpc::{error::Error as RpcError, HttpClient};
+use serde::Serialize;
+use std::{collections::BTreeMap as Map, sync::Mutex, time::Duration};
+use tendermint::Time;
+use tendermint_rpc::{
+    error::{Error as RpcError, ErrorDetail as RpcErrorDetail},
+    HttpClient,
+};
 use tokio::time::timeout;
 use tracing::warn;
 
@@ -11,6 +16,9 @@ pub struct ClientManager {
     /// Map of URLs to their corresponding RPC clients.
     clients: Map<Url, HttpClient>,
 
+    /// Health of each RPC endpoint as observed by the most recent request.
+    endpoints: Mutex<Map<Url, EndpointStatus>>,
+
     /// Duration to use when making requests.
     timeout: Duration,
 }
@@ -22,14 +30,17 @@ impl ClientManager {
     /// Create a new RPC client manager.
     pub fn new(urls: impl IntoIterator<Item = Url>) -> Result<Self, RpcError> {
         let mut clients = Map::new();
+        let mut endpoints = Map::new();
 
         for url in urls {
             let client = HttpClient::new(url.as_str())?;
+            endpoints.insert(url.clone(), EndpointStatus::new(url.clone()));
             clients.insert(url, client);
         }
 
         Ok(Self {
             clients,
+            endpoints: Mutex::new(endpoints),
             timeout: Self::DEFAULT_TIMEOUT,
         })
     }
@@ -39,6 +50,16 @@ impl ClientManager {
         self.clients.values()
     }
 
+    /// Get the health of each RPC endpoint.
+    pub fn endpoint_statuses(&self) -> Vec<EndpointStatus> {
+        self.endpoints
+            .lock()
+            .expect("endpoint status lock poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+
     /// Make a parallel request to all RPC clients.
     pub async fn request<'a, 'b, R, O, F>(&'a self, request: R) -> Vec<Result<O, RpcError>>
     where
@@ -53,14 +74,81 @@ impl ClientManager {
         .await;
 
         let mut responses = Vec::with_capacity(results.len());
+        let mut endpoints = self
+            .endpoints
+            .lock()
+            .expect("endpoint status lock poisoned");
 
         for (url, result) in self.clients.keys().zip(results) {
+            let endpoint = endpoints
+                .entry(url.clone())
+                .or_insert_with(|| EndpointStatus::new(url.clone()));
+
             match result {
-                Ok(response) => responses.push(response),
-                Err(e) => warn!("RPC timeout error for {}: {}", url, e),
+                Ok(response) => {
+                    match &response {
+                        Ok(_) => endpoint.record_success(),
+                        // The endpoint responded, it just didn't have what we asked for
+                        Err(err) if matches!(err.detail(), RpcErrorDetail::Response(_)) => {
+                            endpoint.record_success()
+                        }
+                        Err(err) => endpoint.record_error(rpc_error_message(err)),
+                    }
+
+                    responses.push(response)
+                }
+                Err(e) => {
+                    warn!("RPC timeout error for {}: {}", url, e);
+                    endpoint.record_error(e.to_string());
+                }
             }
         }
 
         responses
     }
 }
+
+/// Health of an individual RPC endpoint.
+#[derive(Clone, Debug, Serialize)]
+pub struct EndpointStatus {
+    /// URL of the RPC endpoint.
+    pub url: Url,
+
+    /// Did the most recent request to this endpoint succeed?
+    pub healthy: bool,
+
+    /// Time of the most recent successful request.
+    pub last_success: Option<Time>,
+
+    /// Error returned by the most recent failed request.
+    pub last_error: Option<String>,
+}
+
+impl EndpointStatus {
+    /// Create a new endpoint status which hasn't been contacted yet.
+    fn new(url: Url) -> Self {
+        Self {
+            url,
+            healthy: false,
+            last_success: None,
+            last_error: None,
+        }
+    }
+
+    /// Record a successful request.
+    fn record_success(&mut self) {
+        self.healthy = true;
+        self.last_success = Some(Time::now());
+    }
+
+    /// Record a failed request.
+    fn record_error(&mut self, error: String) {
+        self.healthy = false;
+        self.last_error = Some(error);
+    }
+}
+
+/// Format an RPC error and its causes as a single line, without a backtrace.
+pub fn rpc_error_message(err: &RpcError) -> String {
+    format!("{:#}", err.trace())
+}
@@ -0,0 +1,340 @@
+//! Mutual monitoring between redundant instances.
+//!
+//! Each instance polls its peers' `/api/cluster` endpoint. The live instance with the lowest ID
+//! is the leader, and is the only one which sends notifications. The others keep tracking alarms
+//! so they can take over as soon as the leader stops responding.
+//!
+//! Followers forward requests to acknowledge alarms or change runtime silences to the leader, and
+//! every instance merges its peers' acknowledgements and runtime silences, so they carry over
+//! whichever instance takes over (including one which restarts with a stale silences file).
+
+use crate::{
+    ack::Ack,
+    config::{ClusterConfig, PeerConfig},
+    error::{Error, ErrorKind},
+    http,
+    pager::{PagerBuffer, PagerRequest, PagerResponse},
+    silence::Silence,
+    status::{StatusBuffer, StatusRequest, StatusResponse},
+};
+use futures::future;
+use hyper::Method;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tendermint::Time;
+use tower::{Service, ServiceExt};
+use tracing::{info, warn};
+
+/// Handle to this instance's view of the cluster.
+#[derive(Clone, Debug)]
+pub struct Cluster(Arc<Mutex<ClusterStatus>>);
+
+impl Cluster {
+    /// Create a cluster of this instance and its peers.
+    ///
+    /// Peers are assumed to be live until they've failed to respond for the peer timeout, so a
+    /// restarted instance doesn't briefly take over from a healthy leader.
+    pub fn new(instance_id: String, peers: &[PeerConfig]) -> Self {
+        let now = Time::now();
+        let mut status = ClusterStatus {
+            instance_id,
+            live: true,
+            leader: String::new(),
+            shared: SharedState::default(),
+            peers: peers
+                .iter()
+                .map(|peer| PeerStatus {
+                    id: peer.id.clone(),
+                    url: peer.url.clone(),
+                    live: true,
+                    leader: None,
+                    last_seen: now,
+                    last_error: None,
+                })
+                .collect(),
+        };
+
+        status.leader = status.elect();
+        Self(Arc::new(Mutex::new(status)))
+    }
+
+    /// Create a cluster of just this instance, which is always the leader.
+    pub fn standalone(instance_id: String) -> Self {
+        Self::new(instance_id, &[])
+    }
+
+    /// Get the ID of this instance.
+    pub fn instance_id(&self) -> String {
+        self.lock().instance_id.clone()
+    }
+
+    /// Is this instance the leader?
+    pub fn is_leader(&self) -> bool {
+        let status = self.lock();
+        status.leader == status.instance_id
+    }
+
+    /// Get this instance's view of the cluster.
+    pub fn status(&self) -> ClusterStatus {
+        self.lock().clone()
+    }
+
+    /// Get the base URL of the leader's HTTP API, unless this instance is the leader.
+    pub fn leader_url(&self) -> Option<String> {
+        let status = self.lock();
+
+        status
+            .peers
+            .iter()
+            .find(|peer| peer.id == status.leader)
+            .map(|peer| peer.url.trim_end_matches('/').to_owned())
+    }
+
+    /// Record this instance's state and the result of polling each peer, and elect a leader.
+    ///
+    /// Returns the shared state of each peer which responded.
+    fn update(
+        &self,
+        live: bool,
+        shared: SharedState,
+        polls: Vec<Result<ClusterStatus, Error>>,
+        peer_timeout: Duration,
+    ) -> Vec<SharedState> {
+        let now = Time::now();
+        let mut status = self.lock();
+        status.live = live;
+        status.shared = shared;
+
+        let mut remote_shared = Vec::new();
+
+        for (peer, result) in status.peers.iter_mut().zip(polls) {
+            match result {
+                Ok(remote) => {
+                    peer.live = remote.live;
+                    peer.leader = Some(remote.leader);
+                    peer.last_seen = now;
+                    peer.last_error = None;
+                    remote_shared.push(remote.shared);
+                }
+                Err(err) => {
+                    warn!("unable to poll peer {}: {err}", peer.id);
+                    peer.live = peer.live
+                        && now
+                            .duration_since(peer.last_seen)
+                            .is_ok_and(|elapsed| elapsed < peer_timeout);
+                    peer.last_error = Some(err.to_string());
+                }
+            }
+        }
+
+        let leader = status.elect();
+
+        if leader != status.leader {
+            info!("{leader} is now the leader (was {})", status.leader);
+            status.leader = leader;
+        }
+
+        remote_shared
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ClusterStatus> {
+        self.0.lock().expect("cluster lock poisoned")
+    }
+}
+
+/// An instance's view of the cluster, served to its peers.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClusterStatus {
+    /// ID of the instance.
+    pub instance_id: String,
+
+    /// Are all of the instance's chain monitors live?
+    pub live: bool,
+
+    /// ID of the instance it considers the leader.
+    pub leader: String,
+
+    /// Acknowledgements and runtime silences of the instance.
+    #[serde(flatten)]
+    pub shared: SharedState,
+
+    /// Status of each of its peers.
+    #[serde(default)]
+    pub peers: Vec<PeerStatus>,
+}
+
+impl ClusterStatus {
+    /// Elect the live instance with the lowest ID as the leader. If no instance is live, this
+    /// instance leads so that alarms are still sent.
+    fn elect(&self) -> String {
+        self.live
+            .then_some(&self.instance_id)
+            .into_iter()
+            .chain(
+                self.peers
+                    .iter()
+                    .filter(|peer| peer.live)
+                    .map(|peer| &peer.id),
+            )
+            .min()
+            .unwrap_or(&self.instance_id)
+            .clone()
+    }
+}
+
+/// Acknowledgements and runtime silences, which instances merge from their peers.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SharedState {
+    /// Acknowledgements which haven't expired.
+    pub acks: Vec<Ack>,
+
+    /// Silences added at runtime, including removed ones which haven't ended yet.
+    pub silences: Vec<Silence>,
+}
+
+/// Status of a peer, as last seen by this instance.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PeerStatus {
+    /// ID of the peer.
+    pub id: String,
+
+    /// Base URL of the peer's HTTP API.
+    pub url: String,
+
+    /// Is the peer responding and are all of its chain monitors live?
+    pub live: bool,
+
+    /// ID of the instance the peer considers the leader.
+    pub leader: Option<String>,
+
+    /// Time the peer last responded.
+    pub last_seen: Time,
+
+    /// Error from the last poll, if it failed.
+    pub last_error: Option<String>,
+}
+
+/// Poll peers every `poll_interval_secs`, electing a new leader whenever the live instances change
+/// and merging their shared state.
+pub async fn run(
+    cluster: Cluster,
+    config: ClusterConfig,
+    mut status_service: StatusBuffer,
+    mut pager_service: PagerBuffer,
+) {
+    loop {
+        let response = status_service
+            .ready()
+            .await
+            .expect("StatusService not ready")
+            .call(StatusRequest::GetHealth)
+            .await
+            .expect("StatusService error");
+
+        let live = match response {
+            StatusResponse::Health(health) => health.live,
+            other => panic!("unexpected StatusService response: {:?}", other),
+        };
+
+        let shared = match pager(&mut pager_service, PagerRequest::GetSharedState).await {
+            PagerResponse::SharedState(shared) => shared,
+            other => panic!("unexpected PagerService response: {:?}", other),
+        };
+
+        let polls = future::join_all(config.peers.iter().map(poll_peer)).await;
+
+        for peer_shared in cluster.update(live, shared, polls, config.peer_timeout()) {
+            pager(
+                &mut pager_service,
+                PagerRequest::MergeSharedState(peer_shared),
+            )
+            .await;
+        }
+
+        tokio::time::sleep(config.poll_interval()).await;
+    }
+}
+
+/// Send a request to the pager service.
+async fn pager(pager_service: &mut PagerBuffer, request: PagerRequest) -> PagerResponse {
+    pager_service
+        .ready()
+        .await
+        .expect("PagerService not ready")
+        .call(request)
+        .await
+        .expect("PagerService error")
+}
+
+/// Get a peer's view of the cluster.
+async fn poll_peer(peer: &PeerConfig) -> Result<ClusterStatus, Error> {
+    let url = format!("{}/api/cluster", peer.url.trim_end_matches('/'));
+    let status = http::request_json::<ClusterStatus>(Method::GET, &url, None).await?;
+
+    if status.instance_id != peer.id {
+        return Err(ErrorKind::Http
+            .context(format!(
+                "{url} is instance '{}', not '{}'",
+                status.instance_id, peer.id
+            ))
+            .into());
+    }
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cluster, SharedState};
+    use crate::{config::PeerConfig, error::ErrorKind};
+    use std::time::Duration;
+
+    #[test]
+    fn elects_lowest_live_instance() {
+        let peer = |id: &str| PeerConfig {
+            id: id.to_owned(),
+            url: format!("http://{id}:9090"),
+        };
+
+        assert!(Cluster::standalone("us-east".to_owned()).is_leader());
+
+        let cluster = Cluster::new("us-east".to_owned(), &[peer("eu-west"), peer("us-west")]);
+        assert!(!cluster.is_leader());
+
+        let mut status = cluster.status();
+        status.peers[0].live = false;
+        assert_eq!(status.elect(), "us-east");
+
+        status.live = false;
+        assert_eq!(status.elect(), "us-west");
+
+        status.peers[1].live = false;
+        assert_eq!(status.elect(), "us-east");
+    }
+
+    #[test]
+    fn merges_responding_peers() {
+        let peer = |id: &str| PeerConfig {
+            id: id.to_owned(),
+            url: format!("http://{id}:9090/"),
+        };
+
+        let cluster = Cluster::new("us-east".to_owned(), &[peer("eu-west"), peer("us-west")]);
+        let remote = cluster.status();
+        let polls = vec![Ok(remote.clone()), Ok(remote)];
+        let shared = cluster.update(true, SharedState::default(), polls, Duration::from_secs(60));
+        assert_eq!(shared.len(), 2);
+        assert_eq!(cluster.leader_url().as_deref(), Some("http://eu-west:9090"));
+
+        let failed = || Err(ErrorKind::Http.context("request timed out").into());
+        let polls = vec![failed(), failed()];
+        let shared = cluster.update(true, SharedState::default(), polls, Duration::ZERO);
+        assert!(shared.is_empty());
+        assert!(cluster.is_leader());
+        assert_eq!(cluster.leader_url(), None);
+    }
+}
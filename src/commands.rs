@@ -2,17 +2,34 @@
 //!
 //! This is where you specify the subcommands of your application.
 //!
-//! The default application comes with two subcommands:
+//! The application comes with the following subcommands:
 //!
 //! - `start`: launches the application
+//! - `tui`: launches the application with an interactive terminal dashboard
+//! - `check`: one-shot check of recent blocks, for use in scripts and cron
+//! - `report`: rank every validator in the set by missed blocks
+//! - `config validate`: check the configuration file for errors
+//! - `config generate`: generate chain configuration from the chain registry
+//! - `silence add|remove|list`: manage silences on a running instance
+//! - `ack`: acknowledge the alarms firing on a chain of a running instance
 //! - `--version`: print application version
 //!
 //! See the `impl Configurable` below for how to specify the path to the
 //! application's configuration file.
 
+mod ack;
+mod api_client;
+mod check;
+mod config;
+mod report;
+mod silence;
 mod start;
+mod tui;
 
-use self::start::StartCmd;
+use self::{
+    ack::AckCmd, check::CheckCmd, config::ConfigCmd, report::ReportCmd, silence::SilenceCmd,
+    start::StartCmd, tui::TuiCmd,
+};
 use crate::config::ObservatoryConfig;
 use abscissa_core::{config::Override, Command, Configurable, FrameworkError, Runnable};
 use std::path::PathBuf;
@@ -26,6 +43,26 @@ pub const CONFIG_FILE: &str = "observatory.toml";
 pub enum ObservatoryCmd {
     /// The `start` subcommand
     Start(StartCmd),
+
+    /// The `tui` subcommand
+    Tui(TuiCmd),
+
+    /// The `check` subcommand
+    Check(CheckCmd),
+
+    /// The `report` subcommand
+    Report(ReportCmd),
+
+    /// The `config` subcommand
+    #[command(subcommand)]
+    Config(ConfigCmd),
+
+    /// The `silence` subcommand
+    #[command(subcommand)]
+    Silence(SilenceCmd),
+
+    /// The `ack` subcommand
+    Ack(AckCmd),
 }
 
 /// Entry point for the application. It needs to be a struct to allow using subcommands!
@@ -44,6 +81,25 @@ pub struct EntryPoint {
     pub config: Option<String>,
 }
 
+impl EntryPoint {
+    /// Log filter overriding the default for commands whose output would be garbled by logs.
+    pub fn log_filter(&self) -> Option<&'static str> {
+        match &self.cmd {
+            ObservatoryCmd::Tui(_) => Some("off"),
+            ObservatoryCmd::Check(_)
+            | ObservatoryCmd::Report(_)
+            | ObservatoryCmd::Config(_)
+            | ObservatoryCmd::Silence(_)
+            | ObservatoryCmd::Ack(_)
+                if !self.verbose =>
+            {
+                Some("off")
+            }
+            _ => None,
+        }
+    }
+}
+
 impl Runnable for EntryPoint {
     fn run(&self) {
         self.cmd.run()
@@ -81,10 +137,15 @@ impl Configurable<ObservatoryConfig> for EntryPoint {
     ) -> Result<ObservatoryConfig, FrameworkError> {
         match &self.cmd {
             ObservatoryCmd::Start(cmd) => cmd.override_config(config),
-            //
-            // If you don't need special overrides for some
-            // subcommands, you can just use a catch all
-            // _ => Ok(config),
+            ObservatoryCmd::Tui(cmd) => cmd.override_config(config),
+            ObservatoryCmd::Check(cmd) => cmd.override_config(config),
+            ObservatoryCmd::Report(cmd) => cmd.override_config(config),
+            ObservatoryCmd::Config(_) | ObservatoryCmd::Silence(_) | ObservatoryCmd::Ack(_) => {
+                Ok(config)
+            } //
+              // If you don't need special overrides for some
+              // subcommands, you can just use a catch all
+              // _ => Ok(config),
         }
     }
 }
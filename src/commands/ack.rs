@@ -0,0 +1,83 @@
+//! `ack` subcommand - acknowledge the alarms firing on a chain through the HTTP API of a running
+//! instance, stopping them from being re-notified
+
+use super::api_client::{exit_on_error, parse_duration, request};
+use crate::{
+    ack::{Ack, AckRequest},
+    pager::AlarmKind,
+    prelude::*,
+};
+use abscissa_core::{Command, Runnable};
+use hyper::Method;
+use std::{env, process, time::Duration};
+use tendermint::chain;
+
+/// `ack` subcommand
+#[derive(clap::Parser, Command, Debug)]
+pub struct AckCmd {
+    /// Chain ID of the alarms to acknowledge
+    chain_id: chain::Id,
+
+    /// Only acknowledge alarms of this kind, e.g. `missed_blocks`
+    #[arg(long, value_parser = parse_kind)]
+    kind: Option<AlarmKind>,
+
+    /// Only acknowledge alarms about the validator (or node) with this name
+    #[arg(long)]
+    subject: Option<String>,
+
+    /// Who is acknowledging the alarms, defaults to `$USER`
+    #[arg(long)]
+    by: Option<String>,
+
+    /// Note about the acknowledgement, e.g. what's being done about it
+    #[arg(long)]
+    note: Option<String>,
+
+    /// How long until the acknowledgement expires, e.g. `30m` or `2h`, defaults to the daemon's
+    /// `ack_ttl_secs`
+    #[arg(long, value_parser = parse_duration)]
+    ttl: Option<Duration>,
+
+    /// Base URL of the observatory HTTP API, defaults to the configured `listen_addr`
+    #[arg(long)]
+    url: Option<String>,
+}
+
+impl Runnable for AckCmd {
+    fn run(&self) {
+        let Some(by) = self.by.clone().or_else(|| env::var("USER").ok()) else {
+            status_err!("couldn't determine who is acknowledging; pass --by");
+            process::exit(1);
+        };
+
+        let ack_request = AckRequest {
+            chain_id: self.chain_id.clone(),
+            kind: self.kind,
+            subject: self.subject.clone(),
+            by,
+            note: self.note.clone(),
+            ttl_secs: self.ttl.map(|ttl| ttl.as_secs()),
+        };
+
+        let body = serde_json::to_string(&ack_request).expect("couldn't serialize ack");
+        let acks: Vec<Ack> =
+            exit_on_error(request(&self.url, Method::POST, "/api/acks", Some(body)));
+
+        for ack in &acks {
+            println!(
+                "acknowledged {} {} {} until {}",
+                ack.chain_id,
+                ack.subject,
+                ack.kind.as_str(),
+                ack.expires.to_rfc3339()
+            );
+        }
+    }
+}
+
+/// Parse an alarm kind, e.g. `missed_blocks`.
+fn parse_kind(s: &str) -> Result<AlarmKind, String> {
+    serde_json::from_value(serde_json::Value::String(s.to_owned()))
+        .map_err(|_| format!("unknown alarm kind '{s}'"))
+}
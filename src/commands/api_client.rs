@@ -0,0 +1,98 @@
+//! Client for the HTTP API of a running instance, used by subcommands which manage it
+
+use crate::{
+    error::{Error, ErrorKind},
+    http,
+    prelude::*,
+};
+use hyper::Method;
+use std::{future::Future, net::Ipv4Addr, process, time::Duration};
+
+/// Run the given request, exiting with an error message if it fails.
+pub(super) fn exit_on_error<T>(future: impl Future<Output = Result<T, Error>>) -> T {
+    let result = abscissa_tokio::run(&APP, future).expect("Tokio runtime crashed");
+
+    result.unwrap_or_else(|err| {
+        status_err!("{}", err);
+        process::exit(1);
+    })
+}
+
+/// Make a request to the HTTP API, deserializing a successful JSON response.
+pub(super) async fn request<T: serde::de::DeserializeOwned>(
+    url: &Option<String>,
+    method: Method,
+    path: &str,
+    body: Option<String>,
+) -> Result<T, Error> {
+    let url = format!("{}{path}", api_url(url)?.trim_end_matches('/'));
+    http::request_json(method, &url, body).await
+}
+
+/// Get the base URL of the HTTP API.
+fn api_url(url: &Option<String>) -> Result<String, Error> {
+    if let Some(url) = url {
+        return Ok(url.clone());
+    }
+
+    let config = APP.config();
+    let mut addr = config
+        .http
+        .as_ref()
+        .ok_or_else(|| {
+            Error::from(
+                ErrorKind::Config.context("no [http] section configured; pass --url instead"),
+            )
+        })?
+        .listen_addr;
+
+    if addr.ip().is_unspecified() {
+        addr.set_ip(Ipv4Addr::LOCALHOST.into());
+    }
+
+    Ok(format!("http://{addr}"))
+}
+
+/// Parse a duration such as `90s`, `30m`, `2h` or `1d`.
+pub(super) fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let number = number
+        .parse::<u64>()
+        .map_err(|_| format!("invalid duration '{s}'"))?;
+
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid duration '{s}' (expected a unit of s, m, h or d)"
+            ))
+        }
+    };
+
+    let secs = number
+        .checked_mul(secs)
+        .ok_or_else(|| format!("duration '{s}' is too large"))?;
+
+    Ok(Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_duration;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_duration("2").is_err());
+        assert!(parse_duration("h").is_err());
+        assert_eq!(
+            parse_duration("99999999999999999d"),
+            Err("duration '99999999999999999d' is too large".to_owned())
+        );
+    }
+}
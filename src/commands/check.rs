@@ -0,0 +1,249 @@
+//! `check` subcommand - one-shot signing check for scripts and cron
+
+use crate::{
+    chain_monitor::ChainMonitor,
+    client_manager::{rpc_error_message, ClientManager},
+    config::{ChainConfig, ObservatoryConfig},
+    health::Heartbeat,
+    prelude::*,
+};
+use abscissa_core::{config, Command, FrameworkError, Runnable};
+use futures::future;
+use serde::Serialize;
+use std::process;
+use tendermint::{account, block, chain};
+
+/// `check` subcommand
+///
+/// Fetches the last N blocks for each configured chain, prints a summary of
+/// missed blocks and exits with a Nagios-style status code:
+///
+/// - `0`: all thresholds are satisfied
+/// - `2`: a threshold was breached on at least one chain
+/// - `3`: at least one chain couldn't be checked
+#[derive(clap::Parser, Command, Debug)]
+pub struct CheckCmd {
+    /// Only check the chain with the given ID
+    #[arg(long)]
+    chain: Option<chain::Id>,
+
+    /// Number of recent blocks to check
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    blocks: u64,
+
+    /// Maximum number of missed blocks before the check fails
+    #[arg(long, default_value_t = 50)]
+    max_missed: usize,
+
+    /// Minimum number of consecutive signed blocks required for the check to pass
+    #[arg(long, default_value_t = 0)]
+    min_recent: usize,
+
+    /// Print results as JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+impl Runnable for CheckCmd {
+    /// Run the check.
+    fn run(&self) {
+        let config = APP.config();
+
+        let chains = config
+            .chains
+            .iter()
+            .filter(|chain| self.chain.as_ref().is_none_or(|id| &chain.id == id))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if chains.is_empty() {
+            status_err!("no matching chains configured (no 'observatory.toml'?)");
+            process::exit(CheckStatus::Unknown.exit_code());
+        }
+
+        let results = abscissa_tokio::run(&APP, async {
+            future::join_all(chains.into_iter().map(|chain| self.check_chain(chain))).await
+        })
+        .expect("Tokio runtime crashed")
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        if self.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&results).expect("couldn't serialize results")
+            );
+        } else {
+            print_table(&results);
+        }
+
+        let status = results
+            .iter()
+            .map(|result| result.status)
+            .max()
+            .unwrap_or(CheckStatus::Ok);
+
+        process::exit(status.exit_code());
+    }
+}
+
+impl config::Override<ObservatoryConfig> for CheckCmd {
+    fn override_config(
+        &self,
+        config: ObservatoryConfig,
+    ) -> Result<ObservatoryConfig, FrameworkError> {
+        Ok(config)
+    }
+}
+
+impl CheckCmd {
+    /// Fetch recent blocks for the given chain and evaluate each validator against the thresholds.
+    async fn check_chain(&self, config: ChainConfig) -> Vec<CheckResult> {
+        let validators = config.validators();
+        let mut results = validators
+            .iter()
+            .map(|validator| CheckResult {
+                chain_id: config.id.clone(),
+                validator: validator.name.clone(),
+                validator_addr: validator.addr,
+                status: CheckStatus::Unknown,
+                height: None,
+                blocks: 0,
+                missed_blocks: 0,
+                recent_blocks: 0,
+                nil_votes: 0,
+                proposed_blocks: 0,
+                missed_proposals: 0,
+                uptime: None,
+                error: None,
+            })
+            .collect::<Vec<_>>();
+
+        let client_manager = match ClientManager::new(config.rpc_urls) {
+            Ok(client_manager) => client_manager,
+            Err(err) => {
+                for result in &mut results {
+                    result.error = Some(rpc_error_message(&err));
+                }
+                return results;
+            }
+        };
+
+        let mut monitor = match ChainMonitor::new(config.id, client_manager, Heartbeat::new()).await
+        {
+            Ok(monitor) => monitor,
+            Err(err) => {
+                for result in &mut results {
+                    result.error = Some(err.to_string());
+                }
+                return results;
+            }
+        };
+        monitor.backfill(self.blocks as usize).await;
+
+        let status = monitor.status(&validators);
+
+        for (result, validator) in results.iter_mut().zip(&status.validators) {
+            if status.blocks.is_empty() {
+                result.error = Some("couldn't fetch any blocks".to_owned());
+                continue;
+            }
+
+            result.height = Some(status.height);
+            result.blocks = status.blocks.len();
+            result.missed_blocks = validator.missed_blocks;
+            result.recent_blocks = validator.recent_blocks;
+            result.nil_votes = validator.nil_votes;
+            result.proposed_blocks = validator.proposed_blocks;
+            result.missed_proposals = validator.missed_proposals;
+            result.uptime = validator.uptime;
+            result.status = if validator.missed_blocks > self.max_missed
+                || validator.recent_blocks < self.min_recent
+            {
+                CheckStatus::Critical
+            } else {
+                CheckStatus::Ok
+            };
+        }
+
+        results
+    }
+}
+
+/// Result of checking a single validator on a chain.
+#[derive(Debug, Serialize)]
+struct CheckResult {
+    chain_id: chain::Id,
+    validator: String,
+    validator_addr: account::Id,
+    status: CheckStatus,
+    height: Option<block::Height>,
+    blocks: usize,
+    missed_blocks: usize,
+    recent_blocks: usize,
+    nil_votes: usize,
+    proposed_blocks: usize,
+    missed_proposals: usize,
+    uptime: Option<f64>,
+    error: Option<String>,
+}
+
+/// Outcome of a check, ordered by severity.
+#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Ok,
+    Critical,
+    Unknown,
+}
+
+impl CheckStatus {
+    /// Nagios-compatible exit code for this status.
+    fn exit_code(self) -> i32 {
+        match self {
+            CheckStatus::Ok => 0,
+            CheckStatus::Critical => 2,
+            CheckStatus::Unknown => 3,
+        }
+    }
+}
+
+/// Print check results as a human-readable table.
+fn print_table(results: &[CheckResult]) {
+    println!(
+        "{:<20} {:<20} {:>12} {:>10} {:>6} {:>8} {:>8}  STATUS",
+        "CHAIN", "VALIDATOR", "HEIGHT", "MISSED", "NIL", "STREAK", "UPTIME"
+    );
+
+    for result in results {
+        let height = result
+            .height
+            .map(|height| height.to_string())
+            .unwrap_or_else(|| "-".to_owned());
+
+        let uptime = result
+            .uptime
+            .map(|uptime| format!("{uptime:.1}%"))
+            .unwrap_or_else(|| "-".to_owned());
+
+        let status = match (&result.error, result.status) {
+            (Some(err), _) => format!("UNKNOWN ({err})"),
+            (None, CheckStatus::Ok) => "OK".to_owned(),
+            (None, CheckStatus::Critical) => "CRITICAL".to_owned(),
+            (None, CheckStatus::Unknown) => "UNKNOWN".to_owned(),
+        };
+
+        println!(
+            "{:<20} {:<20} {:>12} {:>10} {:>6} {:>8} {:>8}  {}",
+            result.chain_id.as_str(),
+            result.validator,
+            height,
+            format!("{}/{}", result.missed_blocks, result.blocks),
+            result.nil_votes,
+            result.recent_blocks,
+            uptime,
+            status
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! `config` subcommand - tools for working with `observatory.toml`
+
+mod generate;
+mod validate;
+
+use self::{generate::GenerateCmd, validate::ValidateCmd};
+use abscissa_core::{Command, Runnable};
+
+/// `config` subcommand
+#[derive(clap::Subcommand, Command, Debug, Runnable)]
+pub enum ConfigCmd {
+    /// Check the configuration file for errors
+    Validate(ValidateCmd),
+
+    /// Generate chain configuration from a chain registry checkout
+    Generate(GenerateCmd),
+}
@@ -0,0 +1,303 @@
+//! `config generate` subcommand - generate `[[chain]]` entries from the chain registry
+
+use crate::{
+    config::ChainConfig,
+    consensus_key::{consensus_address, parse_consensus_pubkey},
+    error::{Error, ErrorKind},
+    http,
+    prelude::*,
+};
+use abscissa_core::{Command, Runnable};
+use hyper::Method;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+use subtle_encoding::bech32;
+use tendermint::{account, chain};
+
+/// `config generate` subcommand
+///
+/// Reads `chain.json` files from a local checkout of the Cosmos chain registry
+/// (<https://github.com/cosmos/chain-registry>) and prints `[[chain]]` entries
+/// for the named chains.
+#[derive(clap::Parser, Command, Debug)]
+pub struct GenerateCmd {
+    /// Path to a local checkout of the chain registry
+    #[arg(long)]
+    registry: PathBuf,
+
+    /// Consensus public key used to derive `validator_addr` for every chain
+    #[arg(long)]
+    consensus_pubkey: Option<String>,
+
+    /// Validator operator address(es); the consensus key is looked up via the
+    /// chain's REST API, matching chains by bech32 prefix
+    #[arg(long)]
+    valoper: Vec<String>,
+
+    /// Write the generated config to the given file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Chain IDs (or chain registry names) to generate entries for
+    #[arg(required = true)]
+    chains: Vec<String>,
+}
+
+impl Runnable for GenerateCmd {
+    fn run(&self) {
+        let result = abscissa_tokio::run(&APP, self.generate()).expect("Tokio runtime crashed");
+
+        let chains = result.unwrap_or_else(|err| {
+            status_err!("{}", err);
+            process::exit(1);
+        });
+
+        let output = toml::to_string(&GeneratedConfig { chains })
+            .expect("couldn't serialize generated config");
+
+        match &self.output {
+            Some(path) => fs::write(path, output).unwrap_or_else(|err| {
+                status_err!("couldn't write {}: {}", path.display(), err);
+                process::exit(1);
+            }),
+            None => print!("{output}"),
+        }
+    }
+}
+
+impl GenerateCmd {
+    /// Generate chain configurations for every requested chain.
+    async fn generate(&self) -> Result<Vec<ChainConfig>, Error> {
+        let registry = load_registry(&self.registry)?;
+        let mut chains = Vec::with_capacity(self.chains.len());
+
+        for name in &self.chains {
+            let entry = registry
+                .iter()
+                .find(|entry| &entry.chain_id == name || &entry.chain_name == name)
+                .ok_or_else(|| config_error(format!("chain '{name}' not found in registry")))?;
+
+            let validator_addr = self.validator_addr(entry).await?;
+            chains.push(entry.chain_config(validator_addr)?);
+        }
+
+        Ok(chains)
+    }
+
+    /// Find the `--valoper` address for the given chain, by its bech32 prefix.
+    fn valoper(&self, entry: &RegistryEntry) -> Option<&String> {
+        let hrp = format!("{}valoper", entry.bech32_prefix.as_ref()?);
+
+        self.valoper.iter().find(|valoper| {
+            bech32::decode(valoper).is_ok_and(|(valoper_hrp, _)| valoper_hrp == hrp)
+        })
+    }
+
+    /// Derive the validator address for the given chain.
+    async fn validator_addr(&self, entry: &RegistryEntry) -> Result<account::Id, Error> {
+        if let Some(valoper) = self.valoper(entry) {
+            return lookup_consensus_address(entry, valoper).await;
+        }
+
+        match &self.consensus_pubkey {
+            Some(pubkey) => Ok(consensus_address(parse_consensus_pubkey(pubkey)?)),
+            None => Err(config_error(format!(
+                "no --valoper or --consensus-pubkey given for {}",
+                entry.chain_id
+            ))),
+        }
+    }
+}
+
+/// Generated configuration file.
+#[derive(Serialize)]
+struct GeneratedConfig {
+    #[serde(rename = "chain")]
+    chains: Vec<ChainConfig>,
+}
+
+/// Subset of a chain registry `chain.json` file.
+#[derive(Debug, Deserialize)]
+struct RegistryEntry {
+    chain_name: String,
+    chain_id: String,
+    bech32_prefix: Option<String>,
+    #[serde(default)]
+    apis: RegistryApis,
+}
+
+impl RegistryEntry {
+    /// Get the configuration for this chain, monitoring the given validator.
+    fn chain_config(&self, validator_addr: account::Id) -> Result<ChainConfig, Error> {
+        let id = chain::Id::try_from(self.chain_id.as_str())
+            .map_err(|err| config_error(format!("invalid chain ID '{}': {err}", self.chain_id)))?;
+
+        Ok(ChainConfig {
+            id,
+            bech32_prefix: self.bech32_prefix.clone(),
+            validator_addr: Some(validator_addr),
+            validators: vec![],
+            rpc_urls: self
+                .apis
+                .rpc
+                .iter()
+                .map(|endpoint| endpoint.address.clone())
+                .collect(),
+            tags: Default::default(),
+            explorer_url: None,
+            upgrades: vec![],
+            discover_upgrades: false,
+        })
+    }
+}
+
+/// API endpoints listed in the chain registry.
+#[derive(Debug, Default, Deserialize)]
+struct RegistryApis {
+    #[serde(default)]
+    rpc: Vec<RegistryEndpoint>,
+    #[serde(default)]
+    rest: Vec<RegistryEndpoint>,
+}
+
+/// Individual API endpoint listed in the chain registry.
+#[derive(Debug, Deserialize)]
+struct RegistryEndpoint {
+    address: String,
+}
+
+/// Load all `chain.json` files in the chain registry, including testnets.
+fn load_registry(path: &Path) -> Result<Vec<RegistryEntry>, Error> {
+    let mut entries = Vec::new();
+
+    for dir in [path.to_owned(), path.join("testnets")] {
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            Err(_) if dir != path => continue,
+            Err(err) => return Err(ErrorKind::Io.context(err).into()),
+        };
+
+        for dir_entry in read_dir {
+            let chain_json = dir_entry?.path().join("chain.json");
+
+            if !chain_json.exists() {
+                continue;
+            }
+
+            let contents = fs::read_to_string(&chain_json)?;
+            let entry = serde_json::from_str(&contents).map_err(|err| {
+                config_error(format!("couldn't parse {}: {err}", chain_json.display()))
+            })?;
+
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Look up a validator's consensus address from its operator address using the chain's REST API.
+async fn lookup_consensus_address(
+    entry: &RegistryEntry,
+    valoper: &str,
+) -> Result<account::Id, Error> {
+    for endpoint in &entry.apis.rest {
+        let url = format!(
+            "{}/cosmos/staking/v1beta1/validators/{valoper}",
+            endpoint.address.trim_end_matches('/')
+        );
+
+        let pubkey = http::request_json::<serde_json::Value>(Method::GET, &url, None)
+            .await
+            .ok()
+            .and_then(|json| json["validator"].get("consensus_pubkey").cloned());
+
+        if let Some(pubkey) = pubkey {
+            return Ok(consensus_address(parse_consensus_pubkey(
+                &pubkey.to_string(),
+            )?));
+        }
+    }
+
+    Err(ErrorKind::Http
+        .context(format!(
+            "couldn't look up {valoper} via any {} REST endpoint",
+            entry.chain_id
+        ))
+        .into())
+}
+
+fn config_error(message: String) -> Error {
+    ErrorKind::Config.context(message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_registry, GenerateCmd, GeneratedConfig};
+    use crate::config::ObservatoryConfig;
+    use std::{fs, path::Path};
+    use subtle_encoding::bech32;
+    use tendermint::account;
+
+    const CHAIN_JSON: &str = r#"{
+        "$schema": "../chain.schema.json",
+        "chain_name": "cosmoshub",
+        "chain_id": "cosmoshub-4",
+        "bech32_prefix": "cosmos",
+        "apis": {
+            "rpc": [{ "address": "https://rpc.cosmos.network", "provider": "Hub" }],
+            "rest": [{ "address": "https://rest.cosmos.network" }]
+        }
+    }"#;
+
+    fn write_chain_json(dir: &Path, contents: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("chain.json"), contents).unwrap();
+    }
+
+    #[test]
+    fn generates_chain_config_from_registry() {
+        let registry =
+            std::env::temp_dir().join(format!("observatory-registry-{}", std::process::id()));
+        write_chain_json(&registry.join("cosmoshub"), CHAIN_JSON);
+        write_chain_json(
+            &registry.join("testnets").join("cosmoshubtestnet"),
+            r#"{ "chain_name": "cosmoshubtestnet", "chain_id": "theta-testnet-001" }"#,
+        );
+        fs::create_dir_all(registry.join("_IBC")).unwrap();
+
+        let mut entries = load_registry(&registry).unwrap();
+        fs::remove_dir_all(&registry).unwrap();
+        entries.sort_by(|a, b| a.chain_id.cmp(&b.chain_id));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].chain_id, "theta-testnet-001");
+        assert!(entries[1].bech32_prefix.is_none());
+
+        let entry = &entries[0];
+        let valoper = bech32::encode("cosmosvaloper", [1; 20]);
+        let cmd = GenerateCmd {
+            registry,
+            consensus_pubkey: None,
+            valoper: vec![bech32::encode("osmovaloper", [1; 20]), valoper.clone()],
+            output: None,
+            chains: vec!["cosmoshub".to_owned()],
+        };
+        assert_eq!(cmd.valoper(entry), Some(&valoper));
+        assert_eq!(cmd.valoper(&entries[1]), None);
+
+        let validator_addr = account::Id::new([2; 20]);
+        let chains = vec![entry.chain_config(validator_addr).unwrap()];
+        let generated = toml::to_string(&GeneratedConfig { chains }).unwrap();
+        let config: ObservatoryConfig = toml::from_str(&generated).unwrap();
+
+        assert_eq!(config.chains.len(), 1);
+        assert_eq!(config.chains[0].id.as_str(), "cosmoshub-4");
+        assert_eq!(config.chains[0].bech32_prefix.as_deref(), Some("cosmos"));
+        assert_eq!(config.chains[0].validator_addr, Some(validator_addr));
+        assert_eq!(config.chains[0].rpc_urls, ["https://rpc.cosmos.network"]);
+    }
+}
@@ -0,0 +1,623 @@
+//! `config validate` subcommand - check `observatory.toml` for errors
+
+use crate::{
+    client_manager::rpc_error_message,
+    config::{ChainConfig, ObservatoryConfig},
+    prelude::*,
+    routing::Router,
+    template,
+};
+use abscissa_core::{Command, Runnable};
+use futures::future;
+use std::{collections::BTreeSet as Set, fmt, process, str::FromStr, time::Duration};
+use tendermint::Time;
+use tendermint_rpc::{Client, HttpClient, Paging, Url};
+use tokio::time::timeout;
+
+/// Amount of time to wait for each probe request.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `config validate` subcommand
+///
+/// Checks the configuration for common mistakes and prints a per-item report.
+/// Exits with a non-zero status if any errors were found.
+#[derive(clap::Parser, Command, Debug)]
+pub struct ValidateCmd {
+    /// Probe each RPC endpoint to confirm it serves the configured chain and
+    /// that the validator is in the current validator set
+    #[arg(long)]
+    probe: bool,
+}
+
+impl Runnable for ValidateCmd {
+    fn run(&self) {
+        let config = APP.config();
+        let mut report = Report::default();
+
+        check_config(&config, &mut report);
+
+        if self.probe {
+            let probes = abscissa_tokio::run(&APP, async {
+                future::join_all(config.chains.iter().map(probe_chain)).await
+            })
+            .expect("Tokio runtime crashed");
+
+            for probe in probes {
+                report.items.extend(probe.items);
+            }
+        }
+
+        for item in &report.items {
+            println!("{item}");
+        }
+
+        let errors = report.count(Level::Error);
+        let warnings = report.count(Level::Warning);
+        println!("\n{errors} error(s), {warnings} warning(s)");
+
+        if errors > 0 {
+            process::exit(1);
+        }
+    }
+}
+
+/// Perform static checks on the configuration which don't require network access.
+fn check_config(config: &ObservatoryConfig, report: &mut Report) {
+    if config.chains.is_empty() {
+        report.error("config", "no chains configured (no 'observatory.toml'?)");
+    }
+
+    let mut chain_ids = Set::new();
+
+    for chain in &config.chains {
+        let subject = chain.id.to_string();
+
+        if !chain_ids.insert(&chain.id) {
+            report.error(&subject, "chain is configured more than once");
+        }
+
+        if chain.rpc_urls.is_empty() {
+            report.error(&subject, "no rpc_urls configured");
+        }
+
+        let validators = chain.validators();
+
+        if validators.is_empty() {
+            report.error(
+                &subject,
+                "no validator_addr or [[chain.validator]] configured",
+            );
+        }
+
+        let mut names = Set::new();
+
+        for validator in &validators {
+            if !names.insert(&validator.name) {
+                report.error(
+                    &format!("{subject} {}", validator.name),
+                    "validator name is used more than once",
+                );
+            }
+        }
+
+        let mut urls = Set::new();
+
+        for url in &chain.rpc_urls {
+            let subject = format!("{subject} {url}");
+
+            if !urls.insert(url) {
+                report.warning(&subject, "duplicate rpc_url");
+                continue;
+            }
+
+            match Url::from_str(url) {
+                Ok(_) => report.ok(&subject, "valid rpc_url"),
+                Err(err) => report.error(
+                    &subject,
+                    &format!("invalid rpc_url: {}", rpc_error_message(&err)),
+                ),
+            }
+        }
+
+        if let Some(Err(err)) = chain.explorer_url.as_deref().map(template::check) {
+            report.error(&subject, &format!("explorer_url: {err}"));
+        }
+    }
+
+    if let Some(Err(err)) = config.alarms.runbook_url.as_deref().map(template::check) {
+        report.error("alarms", &format!("runbook_url: {err}"));
+    }
+
+    if config.alarms.max_voting_power_change <= 0.0 {
+        report.error("alarms", "max_voting_power_change must be greater than 0");
+    }
+
+    let mut node_names = Set::new();
+
+    for node in &config.nodes {
+        let subject = format!("{} node {}", node.chain_id, node.name);
+
+        if !node_names.insert(&node.name) {
+            report.error(&subject, "node name is used more than once");
+        }
+
+        if !chain_ids.contains(&node.chain_id) {
+            report.warning(
+                &subject,
+                "chain isn't configured; lag is only compared against our other nodes",
+            );
+        }
+
+        match Url::from_str(&node.rpc_url) {
+            Ok(_) => report.ok(&subject, "valid rpc_url"),
+            Err(err) => report.error(
+                &subject,
+                &format!("invalid rpc_url: {}", rpc_error_message(&err)),
+            ),
+        }
+    }
+
+    for silence in &config.silences {
+        let subject = match &silence.validator {
+            Some(validator) => format!("{} silence {validator}", silence.chain_id),
+            None => format!("{} silence", silence.chain_id),
+        };
+
+        if silence.start.is_some_and(|start| silence.end <= start) {
+            report.error(&subject, "silence ends before it starts");
+        } else if silence.end <= Time::now() {
+            report.warning(&subject, "silence has already ended and will be ignored");
+        }
+
+        let Some(chain) = config
+            .chains
+            .iter()
+            .find(|chain| chain.id == silence.chain_id)
+        else {
+            report.warning(&subject, "chain isn't configured");
+            continue;
+        };
+
+        if let Some(validator) = &silence.validator {
+            let known = chain
+                .validators()
+                .iter()
+                .any(|config| &config.name == validator)
+                || config.nodes.iter().any(|node| &node.name == validator);
+
+            if !known {
+                report.warning(&subject, "no validator or node with this name");
+            }
+        }
+    }
+
+    if let Some(heartbeat) = &config.heartbeat {
+        let dd_api_key = config
+            .datadog
+            .as_ref()
+            .and_then(|datadog| datadog.dd_api_key.as_ref());
+
+        if heartbeat.url.is_none() && !heartbeat.datadog {
+            report.error("heartbeat", "no url configured and datadog isn't enabled");
+        }
+
+        if heartbeat.datadog && dd_api_key.is_none() {
+            report.error(
+                "heartbeat",
+                "datadog is enabled but no [datadog] dd_api_key is configured",
+            );
+        }
+
+        if let Some(url) = &heartbeat.url {
+            match url.parse::<hyper::Uri>() {
+                Ok(uri) if uri.scheme().is_some() => report.ok("heartbeat", "valid url"),
+                Ok(_) => report.error("heartbeat", "url has no scheme"),
+                Err(err) => report.error("heartbeat", &format!("invalid url: {err}")),
+            }
+        }
+    }
+
+    if let Some(cluster) = &config.cluster {
+        if config.http.is_none() {
+            report.error(
+                "cluster",
+                "no [http] configured; peers can't poll this instance",
+            );
+        }
+
+        if cluster.peers.is_empty() {
+            report.warning("cluster", "no [[cluster.peer]] configured");
+        }
+
+        for (i, peer) in cluster.peers.iter().enumerate() {
+            let subject = format!("peer {}", peer.id);
+
+            if peer.id == cluster.instance_id {
+                report.error(&subject, "peer has the same ID as this instance");
+            } else if cluster.peers[..i].iter().any(|other| other.id == peer.id) {
+                report.error(&subject, "peer is configured more than once");
+            }
+
+            match peer.url.parse::<hyper::Uri>() {
+                Ok(uri) if uri.scheme().is_some() => report.ok(&subject, "valid url"),
+                Ok(_) => report.error(&subject, "url has no scheme"),
+                Err(err) => report.error(&subject, &format!("invalid url: {err}")),
+            }
+        }
+    }
+
+    for sink in &config.sinks {
+        if let Some(rate_limit) = &sink.rate_limit {
+            let subject = format!("sink {}", sink.name);
+
+            if rate_limit.max_notifications == 0 {
+                report.error(&subject, "rate limit allows no notifications");
+            }
+
+            if rate_limit.per_secs == 0 {
+                report.error(&subject, "rate limit per_secs must be greater than 0");
+            }
+        }
+    }
+
+    match Router::new(config) {
+        Ok(router) if !router.has_sinks() => report.warning(
+            "sinks",
+            "no [[sink]] or [datadog] dd_api_key configured; alarms can't be sent",
+        ),
+        Ok(_) => report.ok("sinks", "valid sinks and routes"),
+        Err(err) => report.error("sinks", &err.to_string()),
+    }
+
+    for (i, route) in config.routes.iter().enumerate() {
+        let subject = format!("route {}", i + 1);
+
+        for (key, value) in &route.tags {
+            if !config
+                .chains
+                .iter()
+                .any(|chain| chain.tags.get(key) == Some(value))
+            {
+                report.warning(&subject, &format!("no chain is tagged {key} = \"{value}\""));
+            }
+        }
+    }
+
+    if config.routes.last().is_some_and(|last| {
+        !last.chain_ids.is_empty()
+            || !last.kinds.is_empty()
+            || last.severity.is_some()
+            || !last.tags.is_empty()
+    }) {
+        report.warning(
+            "routes",
+            "last route doesn't match every alarm; unmatched alarms aren't sent",
+        );
+    }
+}
+
+/// Probe every RPC endpoint for the given chain.
+async fn probe_chain(chain: &ChainConfig) -> Report {
+    let mut report = Report::default();
+    let mut latest_height = None;
+    let mut client_for_validators = None;
+
+    for url in &chain.rpc_urls {
+        let subject = format!("{} {url}", chain.id);
+
+        let client = match HttpClient::new(url.as_str()) {
+            Ok(client) => client,
+            // Invalid URLs were already reported by `check_config`
+            Err(_) => continue,
+        };
+
+        let status = match timeout(PROBE_TIMEOUT, client.status()).await {
+            Ok(Ok(status)) => status,
+            Ok(Err(err)) => {
+                report.error(
+                    &subject,
+                    &format!("/status request failed: {}", rpc_error_message(&err)),
+                );
+                continue;
+            }
+            Err(_) => {
+                report.error(&subject, "/status request timed out");
+                continue;
+            }
+        };
+
+        if status.node_info.network != chain.id {
+            report.error(
+                &subject,
+                &format!(
+                    "endpoint serves chain '{}', not '{}'",
+                    status.node_info.network, chain.id
+                ),
+            );
+            continue;
+        }
+
+        if status.sync_info.catching_up {
+            report.warning(&subject, "node is catching up");
+        } else {
+            report.ok(
+                &subject,
+                &format!(
+                    "serves {} at height {}",
+                    chain.id, status.sync_info.latest_block_height
+                ),
+            );
+        }
+
+        if latest_height.is_none_or(|height| status.sync_info.latest_block_height > height) {
+            latest_height = Some(status.sync_info.latest_block_height);
+            client_for_validators = Some(client);
+        }
+    }
+
+    let (Some(height), Some(client)) = (latest_height, client_for_validators) else {
+        report.error(
+            chain.id.as_str(),
+            "no endpoints available to check the validator set",
+        );
+        return report;
+    };
+
+    let validator_set = match timeout(PROBE_TIMEOUT, client.validators(height, Paging::All)).await {
+        Ok(Ok(response)) => response.validators,
+        Ok(Err(err)) => {
+            report.error(
+                chain.id.as_str(),
+                &format!("/validators request failed: {}", rpc_error_message(&err)),
+            );
+            return report;
+        }
+        Err(_) => {
+            report.error(chain.id.as_str(), "/validators request timed out");
+            return report;
+        }
+    };
+
+    for validator in chain.validators() {
+        let subject = format!("{} {} ({})", chain.id, validator.name, validator.addr);
+
+        if validator_set
+            .iter()
+            .any(|info| info.address == validator.addr)
+        {
+            report.ok(&subject, "validator is in the current validator set");
+        } else {
+            report.error(
+                &subject,
+                &format!("validator is not in the validator set at height {height}"),
+            );
+        }
+    }
+
+    report
+}
+
+/// Validation report.
+#[derive(Debug, Default)]
+struct Report {
+    items: Vec<ReportItem>,
+}
+
+impl Report {
+    fn ok(&mut self, subject: &str, message: &str) {
+        self.push(Level::Ok, subject, message);
+    }
+
+    fn warning(&mut self, subject: &str, message: &str) {
+        self.push(Level::Warning, subject, message);
+    }
+
+    fn error(&mut self, subject: &str, message: &str) {
+        self.push(Level::Error, subject, message);
+    }
+
+    fn push(&mut self, level: Level, subject: &str, message: &str) {
+        self.items.push(ReportItem {
+            level,
+            subject: subject.to_owned(),
+            message: message.to_owned(),
+        });
+    }
+
+    fn count(&self, level: Level) -> usize {
+        self.items.iter().filter(|item| item.level == level).count()
+    }
+}
+
+/// Individual item in a validation report.
+#[derive(Debug)]
+struct ReportItem {
+    level: Level,
+    subject: String,
+    message: String,
+}
+
+impl fmt::Display for ReportItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            Level::Ok => "ok",
+            Level::Warning => "warn",
+            Level::Error => "error",
+        };
+
+        write!(f, "[{level:>5}] {}: {}", self.subject, self.message)
+    }
+}
+
+/// Severity of a report item.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Level {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_config, Level, Report};
+    use crate::config::ObservatoryConfig;
+
+    fn parse(toml: &str) -> ObservatoryConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn reports_duplicate_chains_and_bad_urls() {
+        let config = parse(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            validator_addr = "95E060D07713070FE9822F6C50BD76BCCBF9F17A"
+            rpc_urls = ["https://cosmos-rpc.polkachu.com/", "not a url"]
+
+            [[chain]]
+            id = "cosmoshub-4"
+            validator_addr = "95E060D07713070FE9822F6C50BD76BCCBF9F17A"
+            rpc_urls = []
+
+            [datadog]
+            dd_api_key = "key"
+            "#,
+        );
+
+        let mut report = Report::default();
+        check_config(&config, &mut report);
+
+        let errors = report
+            .items
+            .iter()
+            .filter(|item| item.level == Level::Error)
+            .map(|item| item.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(errors.len(), 3);
+        assert!(errors.iter().any(|msg| msg.starts_with("invalid rpc_url")));
+        assert!(errors.contains(&"chain is configured more than once"));
+        assert!(errors.contains(&"no rpc_urls configured"));
+    }
+
+    #[test]
+    fn reports_missing_and_duplicate_validators() {
+        let config = parse(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            rpc_urls = ["https://cosmos-rpc.polkachu.com/"]
+
+            [[chain]]
+            id = "osmosis-1"
+            rpc_urls = ["https://osmosis-rpc.polkachu.com/"]
+
+            [[chain.validator]]
+            name = "main"
+            addr = "95E060D07713070FE9822F6C50BD76BCCBF9F17A"
+
+            [[chain.validator]]
+            name = "main"
+            addr = "6B7C7EE945EA4D84AE8F2A259BB15F17830E6182"
+            "#,
+        );
+
+        assert!(config.chains[0].validators().is_empty());
+        assert_eq!(config.chains[1].validators().len(), 2);
+
+        let mut report = Report::default();
+        check_config(&config, &mut report);
+
+        let errors = report
+            .items
+            .iter()
+            .filter(|item| item.level == Level::Error)
+            .map(|item| item.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            [
+                "no validator_addr or [[chain.validator]] configured",
+                "validator name is used more than once"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_heartbeat_without_target() {
+        let config = parse(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            validator_addr = "95E060D07713070FE9822F6C50BD76BCCBF9F17A"
+            rpc_urls = ["https://cosmos-rpc.polkachu.com/"]
+
+            [heartbeat]
+            interval_secs = 30
+            "#,
+        );
+
+        let mut report = Report::default();
+        check_config(&config, &mut report);
+
+        let errors = report
+            .items
+            .iter()
+            .filter(|item| item.level == Level::Error)
+            .map(|item| item.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(errors, ["no url configured and datadog isn't enabled"]);
+    }
+
+    #[test]
+    fn reports_invalid_cluster_peers() {
+        let config = parse(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            validator_addr = "95E060D07713070FE9822F6C50BD76BCCBF9F17A"
+            rpc_urls = ["https://cosmos-rpc.polkachu.com/"]
+
+            [http]
+            listen_addr = "0.0.0.0:9090"
+
+            [cluster]
+            instance_id = "us-east"
+
+            [[cluster.peer]]
+            id = "us-east"
+            url = "http://observatory-2:9090"
+
+            [[cluster.peer]]
+            id = "eu-west"
+            url = "observatory-3:9090"
+            "#,
+        );
+
+        let mut report = Report::default();
+        check_config(&config, &mut report);
+
+        let errors = report
+            .items
+            .iter()
+            .filter(|item| item.level == Level::Error)
+            .map(|item| item.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            ["peer has the same ID as this instance", "url has no scheme"]
+        );
+    }
+
+    #[test]
+    fn accepts_example_config() {
+        let config = parse(include_str!("../../../observatory.toml.example"));
+        let mut report = Report::default();
+        check_config(&config, &mut report);
+        assert_eq!(report.count(Level::Error), 0);
+    }
+}
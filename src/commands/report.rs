@@ -0,0 +1,182 @@
+//! `report` subcommand - rank every validator in the set by missed blocks
+
+use crate::{
+    chain_monitor::ChainMonitor,
+    client_manager::{rpc_error_message, ClientManager},
+    config::{ChainConfig, ObservatoryConfig},
+    health::Heartbeat,
+    prelude::*,
+    signing_report::SigningReport,
+};
+use abscissa_core::{config, Command, FrameworkError, Runnable};
+use futures::future;
+use std::process;
+use tendermint::chain;
+
+/// `report` subcommand
+///
+/// Fetches the last N blocks for each configured chain and ranks every member
+/// of the current validator set by missed blocks, which helps distinguish
+/// network-wide degradation from a problem specific to our validators.
+#[derive(clap::Parser, Command, Debug)]
+pub struct ReportCmd {
+    /// Only report on the chain with the given ID
+    #[arg(long)]
+    chain: Option<chain::Id>,
+
+    /// Number of recent blocks to evaluate
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    blocks: u64,
+
+    /// Maximum number of validators to list per chain
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+
+    /// Print results as JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+impl Runnable for ReportCmd {
+    /// Run the report.
+    fn run(&self) {
+        let config = APP.config();
+
+        let chains = config
+            .chains
+            .iter()
+            .filter(|chain| self.chain.as_ref().is_none_or(|id| &chain.id == id))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if chains.is_empty() {
+            status_err!("no matching chains configured (no 'observatory.toml'?)");
+            process::exit(1);
+        }
+
+        let results = abscissa_tokio::run(&APP, async {
+            future::join_all(chains.into_iter().map(|chain| self.report_chain(chain))).await
+        })
+        .expect("Tokio runtime crashed");
+
+        let mut failed = false;
+        let mut reports = Vec::with_capacity(results.len());
+
+        for result in results {
+            match result {
+                Ok(mut report) => {
+                    if self.json {
+                        report.validators.truncate(self.limit);
+                    }
+
+                    reports.push(report);
+                }
+                Err(err) => {
+                    status_err!("{}", err);
+                    failed = true;
+                }
+            }
+        }
+
+        if self.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&reports).expect("couldn't serialize reports")
+            );
+        } else {
+            for report in &reports {
+                self.print_table(report);
+            }
+        }
+
+        if failed {
+            process::exit(1);
+        }
+    }
+}
+
+impl config::Override<ObservatoryConfig> for ReportCmd {
+    fn override_config(
+        &self,
+        config: ObservatoryConfig,
+    ) -> Result<ObservatoryConfig, FrameworkError> {
+        Ok(config)
+    }
+}
+
+impl ReportCmd {
+    /// Fetch recent blocks and the validator set for the given chain and compute a report.
+    async fn report_chain(&self, config: ChainConfig) -> Result<SigningReport, String> {
+        let chain_id = config.id.clone();
+        let validators = config.validators();
+
+        let client_manager = ClientManager::new(config.rpc_urls)
+            .map_err(|err| format!("[{chain_id}] {}", rpc_error_message(&err)))?;
+
+        let mut monitor = ChainMonitor::new(config.id, client_manager, Heartbeat::new())
+            .await
+            .map_err(|err| format!("[{chain_id}] {err}"))?;
+        monitor.backfill(self.blocks as usize).await;
+
+        monitor
+            .signing_report(&validators)
+            .ok_or_else(|| format!("[{chain_id}] couldn't fetch the validator set"))
+    }
+
+    /// Print a report as a human-readable table.
+    fn print_table(&self, report: &SigningReport) {
+        let average_uptime = report
+            .average_uptime
+            .map(|uptime| format!("{uptime:.1}%"))
+            .unwrap_or_else(|| "-".to_owned());
+
+        println!(
+            "{} at height {}: {} validators, {} blocks, average uptime {}\n",
+            report.chain_id,
+            report.height,
+            report.validators.len(),
+            report.blocks,
+            average_uptime
+        );
+
+        println!(
+            "{:>4}  {:<40} {:>14} {:>8} {:>6} {:>9} {:>8}",
+            "RANK", "VALIDATOR", "POWER", "MISSED", "NIL", "PROPOSED", "UPTIME"
+        );
+
+        for (index, stats) in report.validators.iter().take(self.limit).enumerate() {
+            let marker = if stats.monitored.is_some() { "*" } else { "" };
+            let uptime = stats
+                .uptime
+                .map(|uptime| format!("{uptime:.1}%"))
+                .unwrap_or_else(|| "-".to_owned());
+
+            println!(
+                "{:>4}  {:<40} {:>14} {:>8} {:>6} {:>9} {:>8}",
+                index + 1,
+                format!("{}{marker}", stats.display_name()),
+                stats.voting_power,
+                stats.missed_blocks,
+                stats.nil_votes,
+                stats.proposed_blocks,
+                uptime
+            );
+        }
+
+        for stats in report
+            .validators
+            .iter()
+            .filter(|stats| stats.monitored.is_some())
+        {
+            let rank = report.rank(stats.address).expect("validator not in report");
+            println!(
+                "\n* {} ranks {rank} of {} with {} missed blocks",
+                stats.display_name(),
+                report.validators.len(),
+                stats.missed_blocks
+            );
+        }
+
+        println!();
+    }
+}
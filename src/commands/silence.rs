@@ -0,0 +1,172 @@
+//! `silence` subcommand - manage silences on a running instance through its HTTP API
+
+use super::api_client::{exit_on_error, parse_duration, request};
+use crate::{config::SilenceConfig, silence::Silence};
+use abscissa_core::{Command, Runnable};
+use hyper::Method;
+use std::time::Duration;
+use tendermint::{chain, Time};
+
+/// `silence` subcommand
+#[derive(clap::Subcommand, Command, Debug, Runnable)]
+pub enum SilenceCmd {
+    /// Silence notifications for a chain or validator
+    Add(AddCmd),
+
+    /// Remove a silence
+    Remove(RemoveCmd),
+
+    /// List silences
+    List(ListCmd),
+}
+
+/// `silence add` subcommand
+#[derive(clap::Parser, Command, Debug)]
+pub struct AddCmd {
+    /// Chain ID to silence
+    chain_id: chain::Id,
+
+    /// Only silence alarms for the validator (or node) with this name
+    #[arg(long)]
+    validator: Option<String>,
+
+    /// Time the silence starts (RFC 3339), defaults to now
+    #[arg(long, value_parser = parse_time)]
+    start: Option<Time>,
+
+    /// Time the silence ends (RFC 3339)
+    #[arg(long, value_parser = parse_time, required_unless_present = "duration")]
+    end: Option<Time>,
+
+    /// How long the silence lasts from its start, e.g. `30m`, `2h` or `1d`
+    #[arg(long, value_parser = parse_duration, conflicts_with = "end")]
+    duration: Option<Duration>,
+
+    /// Why the alarms are silenced
+    #[arg(long)]
+    reason: String,
+
+    /// Base URL of the observatory HTTP API, defaults to the configured `listen_addr`
+    #[arg(long)]
+    url: Option<String>,
+}
+
+impl Runnable for AddCmd {
+    fn run(&self) {
+        let end = match (self.end, self.duration) {
+            (Some(end), _) => end,
+            (None, Some(duration)) => (self.start.unwrap_or_else(Time::now) + duration)
+                .expect("silence end time out of range"),
+            (None, None) => unreachable!("clap requires --end or --duration"),
+        };
+
+        let config = SilenceConfig {
+            chain_id: self.chain_id.clone(),
+            validator: self.validator.clone(),
+            start: self.start,
+            end,
+            reason: self.reason.clone(),
+        };
+
+        let body = serde_json::to_string(&config).expect("couldn't serialize silence");
+        let silence: Silence = exit_on_error(request(
+            &self.url,
+            Method::POST,
+            "/api/silences",
+            Some(body),
+        ));
+
+        println!(
+            "added silence {} for {} until {}",
+            silence.id,
+            subject(&silence),
+            silence.end.to_rfc3339()
+        );
+    }
+}
+
+/// `silence remove` subcommand
+#[derive(clap::Parser, Command, Debug)]
+pub struct RemoveCmd {
+    /// ID of the silence to remove
+    id: String,
+
+    /// Base URL of the observatory HTTP API, defaults to the configured `listen_addr`
+    #[arg(long)]
+    url: Option<String>,
+}
+
+impl Runnable for RemoveCmd {
+    fn run(&self) {
+        let path = format!("/api/silences/{}", self.id);
+        let silence: Silence = exit_on_error(request(&self.url, Method::DELETE, &path, None));
+        println!("removed silence {} for {}", silence.id, subject(&silence));
+    }
+}
+
+/// `silence list` subcommand
+#[derive(clap::Parser, Command, Debug)]
+pub struct ListCmd {
+    /// Print silences as JSON instead of a table
+    #[arg(long)]
+    json: bool,
+
+    /// Base URL of the observatory HTTP API, defaults to the configured `listen_addr`
+    #[arg(long)]
+    url: Option<String>,
+}
+
+impl Runnable for ListCmd {
+    fn run(&self) {
+        let silences: Vec<Silence> =
+            exit_on_error(request(&self.url, Method::GET, "/api/silences", None));
+
+        if self.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&silences).expect("couldn't serialize silences")
+            );
+            return;
+        }
+
+        let now = Time::now();
+
+        println!(
+            "{:<10} {:<8} {:<24} {:<32} {:<32} REASON",
+            "ID", "STATE", "SUBJECT", "START", "END"
+        );
+
+        for silence in &silences {
+            let state = if silence.is_active(now) {
+                "active"
+            } else if silence.end <= now {
+                "ended"
+            } else {
+                "pending"
+            };
+
+            println!(
+                "{:<10} {:<8} {:<24} {:<32} {:<32} {}",
+                silence.id,
+                state,
+                subject(silence),
+                silence.start.to_rfc3339(),
+                silence.end.to_rfc3339(),
+                silence.reason
+            );
+        }
+    }
+}
+
+/// Describe what a silence applies to.
+fn subject(silence: &Silence) -> String {
+    match &silence.validator {
+        Some(validator) => format!("{} {validator}", silence.chain_id),
+        None => silence.chain_id.to_string(),
+    }
+}
+
+/// Parse an RFC 3339 timestamp.
+fn parse_time(s: &str) -> Result<Time, String> {
+    Time::parse_from_rfc3339(s).map_err(|err| err.to_string())
+}
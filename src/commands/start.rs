@@ -1,18 +1,40 @@
 //! `start` subcommand - example of how to write a subcommand
 
 use crate::{
+    api,
     chain_monitor::ChainMonitor,
     client_manager::ClientManager,
-    config::{ChainConfig, ObservatoryConfig},
-    pager::{monitor_pager_service, PagerBuffer, PagerRequest, PagerService},
+    cluster::{self, Cluster},
+    config::{AlarmsConfig, ChainConfig, NodeConfig, ObservatoryConfig},
+    consensus_monitor::{self, ConsensusMonitor},
+    dispatch::Dispatcher,
+    health::Heartbeat,
+    heartbeat,
+    node_monitor::{self, NodeMonitor},
+    pager::{
+        monitor_pager_service, AlarmDetails, AlarmKind, ChainEvent, ConsensusEvent, NodeEvent,
+        PagerAlarm, PagerBuffer, PagerRequest, PagerService, Severity, SigningEvent,
+    },
     prelude::*,
+    routing::Router,
+    silence::Silences,
+    status::{StatusBuffer, StatusRequest, StatusResponse, StatusService},
+    template::Links,
+    upgrade,
 };
 use abscissa_core::{config, Command, FrameworkError, Runnable};
 use futures::future;
-use std::time::Duration;
-use tokio::task::JoinHandle;
+use std::{sync::Arc, time::Duration};
+use tendermint::Time;
+use tokio::{sync::Notify, task::JoinHandle};
 use tower::{Service, ServiceExt};
 
+/// How often each chain is checked for halts.
+const HALT_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of halt checks between queries for the upgrade module's current plan.
+const UPGRADE_DISCOVERY_CHECKS: u64 = 30;
+
 /// `start` subcommand
 ///
 /// The `Parser` proc macro generates an option parser based on the struct
@@ -31,28 +53,80 @@ impl Runnable for StartCmd {
     fn run(&self) {
         let config = APP.config();
         let alerting_interval = Duration::from_secs(120);
-        let missing_blocks_threshold = 50;
-        let recovered_after_threshold = 5;
 
         if config.chains.is_empty() {
             panic!("no chains configured (no 'observatory.toml'?)");
         }
 
+        let router =
+            Router::new(&config).unwrap_or_else(|err| panic!("invalid alarm routing: {err}"));
+
+        let dd_api_key = config
+            .datadog
+            .as_ref()
+            .and_then(|datadog| datadog.dd_api_key.clone());
+
+        if let Some(heartbeat_config) = &config.heartbeat {
+            heartbeat::check(heartbeat_config, dd_api_key.as_deref())
+                .unwrap_or_else(|err| panic!("invalid heartbeat: {err}"));
+        }
+
+        let cluster = match &config.cluster {
+            Some(cluster_config) => {
+                Cluster::new(cluster_config.instance_id.clone(), &cluster_config.peers)
+            }
+            None => Cluster::standalone(
+                hostname::get()
+                    .expect("couldn't get hostname")
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+        };
+
         abscissa_tokio::run(&APP, async {
-            let pager_service = tower::ServiceBuilder::new()
-                .buffer(config.chains.len() * 2) // heuristic
-                .service(PagerService::new(
-                    missing_blocks_threshold,
-                    recovered_after_threshold,
-                ));
+            let Monitors {
+                mut futures,
+                pager_service,
+                pager_notify,
+                status_service,
+            } = spawn_monitors(&config).await;
 
-            let mut futures = Vec::new();
+            futures.push(
+                init_pager_monitor(
+                    alerting_interval,
+                    pager_service.clone(),
+                    pager_notify,
+                    Dispatcher::new(router, Links::new(&config), config.alarms.group_window()),
+                    cluster.clone(),
+                )
+                .await,
+            );
 
-            for chain_config in &config.chains {
-                futures.push(run_monitor(chain_config.clone(), pager_service.clone()).await);
+            if let Some(cluster_config) = &config.cluster {
+                futures.push(tokio::spawn(cluster::run(
+                    cluster.clone(),
+                    cluster_config.clone(),
+                    status_service.clone(),
+                    pager_service.clone(),
+                )));
             }
 
-            futures.push(init_pager_monitor(alerting_interval, pager_service.clone()).await);
+            if let Some(heartbeat_config) = &config.heartbeat {
+                futures.push(tokio::spawn(heartbeat::run(
+                    heartbeat_config.clone(),
+                    dd_api_key.clone(),
+                    status_service.clone(),
+                )));
+            }
+
+            if let Some(http_config) = &config.http {
+                futures.push(tokio::spawn(api::serve(
+                    http_config.listen_addr,
+                    status_service.clone(),
+                    pager_service.clone(),
+                    cluster.clone(),
+                )));
+            }
 
             future::join_all(futures).await;
         })
@@ -72,36 +146,422 @@ impl config::Override<ObservatoryConfig> for StartCmd {
     }
 }
 
-async fn run_monitor(config: ChainConfig, mut pager_service: PagerBuffer) -> JoinHandle<()> {
+/// Chain monitors running in the background along with the services they report to.
+pub(super) struct Monitors {
+    /// Handles to the spawned monitor tasks.
+    pub futures: Vec<JoinHandle<()>>,
+
+    /// Pager service which monitors report signing events to.
+    pub pager_service: PagerBuffer,
+
+    /// Notified by the pager service when a new critical alarm is raised.
+    pub pager_notify: Arc<Notify>,
+
+    /// Status service which monitors report their state to.
+    pub status_service: StatusBuffer,
+}
+
+/// Spawn a monitor for every configured chain and node.
+pub(super) async fn spawn_monitors(config: &ObservatoryConfig) -> Monitors {
+    let missing_blocks_threshold = 50;
+    let recovered_after_threshold = 5;
+    let nil_votes_threshold = 0.1;
+
+    let mut silences = Silences::load(&config.silences, config.silences_path.clone())
+        .unwrap_or_else(|err| panic!("couldn't load silences: {err}"));
+
+    if let Some(cluster_config) = &config.cluster {
+        silences = silences.with_id_prefix(cluster_config.instance_id.clone());
+    }
+
+    let pager = PagerService::new(
+        missing_blocks_threshold,
+        recovered_after_threshold,
+        nil_votes_threshold,
+        config.alarms.max_voting_power_change,
+    )
+    .with_silences(silences)
+    .with_ack_ttl(config.alarms.ack_ttl());
+    let pager_notify = pager.notifier();
+
+    let pager_service = tower::ServiceBuilder::new()
+        .buffer((config.chains.len() * 3 + config.nodes.len()) * 2) // heuristic
+        .service(pager);
+
+    let status_service = tower::ServiceBuilder::new()
+        .buffer((config.chains.len() * 3 + config.nodes.len()) * 2) // heuristic
+        .service(StatusService::new(
+            config.chains.iter().map(|chain| chain.id.clone()).collect(),
+            config.health.clone(),
+        ));
+
+    let mut futures = Vec::new();
+
+    for chain_config in &config.chains {
+        if chain_config.validators().is_empty() {
+            warn!(
+                "[{}] no validator_addr or [[chain.validator]] configured; \
+                 only chain-wide alarms will be raised",
+                chain_config.id
+            );
+        }
+
+        futures.push(
+            run_monitor(
+                chain_config.clone(),
+                config.alarms.clone(),
+                pager_service.clone(),
+                status_service.clone(),
+            )
+            .await,
+        );
+    }
+
+    for chain_config in &config.chains {
+        // Our own nodes are polled along with the chain's RPC endpoints
+        let rpc_urls = chain_config
+            .rpc_urls
+            .iter()
+            .chain(
+                config
+                    .nodes
+                    .iter()
+                    .filter(|node| node.chain_id == chain_config.id)
+                    .map(|node| &node.rpc_url),
+            )
+            .cloned()
+            .collect::<Vec<_>>();
+
+        futures.push(
+            run_halt_monitor(
+                chain_config.clone(),
+                config.alarms.clone(),
+                pager_service.clone(),
+                status_service.clone(),
+            )
+            .await,
+        );
+
+        futures.push(
+            run_consensus_monitor(
+                chain_config.clone(),
+                rpc_urls,
+                config.alarms.max_round,
+                pager_service.clone(),
+                status_service.clone(),
+            )
+            .await,
+        );
+    }
+
+    for node_config in &config.nodes {
+        futures.push(
+            run_node_monitor(
+                node_config.clone(),
+                pager_service.clone(),
+                status_service.clone(),
+            )
+            .await,
+        );
+    }
+
+    Monitors {
+        futures,
+        pager_service,
+        pager_notify,
+        status_service,
+    }
+}
+
+async fn run_monitor(
+    config: ChainConfig,
+    alarms_config: AlarmsConfig,
+    mut pager_service: PagerBuffer,
+    mut status_service: StatusBuffer,
+) -> JoinHandle<()> {
     tokio::spawn(async move {
+        let validators = config.validators();
         let chain_id = config.id;
-        let validator_addr = config.validator_addr;
         let rpc_urls = config.rpc_urls;
 
-        info!("[{chain_id}] monitoring signatures from {validator_addr}");
+        for validator in &validators {
+            info!(
+                "[{chain_id}] monitoring signatures from {} ({})",
+                validator.name, validator.addr
+            );
+        }
 
         let client_manager =
             ClientManager::new(rpc_urls).expect("couldn't initialize RPC client manager");
 
-        let mut monitor = ChainMonitor::new(chain_id.clone(), client_manager).await;
+        let heartbeat = Heartbeat::new();
+
+        status_service
+            .ready()
+            .await
+            .expect("StatusService not ready")
+            .call(StatusRequest::Register {
+                chain_id: chain_id.clone(),
+                heartbeat: heartbeat.clone(),
+            })
+            .await
+            .expect("StatusService error");
+
+        let mut monitor = ChainMonitor::new(chain_id.clone(), client_manager, heartbeat)
+            .await
+            .unwrap_or_else(|err| panic!("[{chain_id}] {err}"));
 
         loop {
+            status_service
+                .ready()
+                .await
+                .expect("StatusService not ready")
+                .call(StatusRequest::Update(monitor.status(&validators)))
+                .await
+                .expect("StatusService error");
+
             monitor.fetch_next_block().await;
 
-            let missed_blocks = monitor.missed_blocks(validator_addr);
-            let recent_blocks = monitor.recent_blocks(validator_addr);
+            let misbehavior = monitor.misbehavior();
+
+            for validator in &validators {
+                let event = SigningEvent {
+                    chain_id: chain_id.clone(),
+                    validator: validator.name.clone(),
+                    moniker: monitor.moniker(validator.addr).map(str::to_owned),
+                    height: monitor.height(),
+                    blocks: monitor.commit_count(),
+                    missed_blocks: monitor.missed_blocks(validator.addr),
+                    recent_blocks: monitor.recent_blocks(validator.addr),
+                    nil_votes: monitor.nil_votes(validator.addr),
+                    missed_proposal: monitor
+                        .last_missed_proposal(validator.addr)
+                        .filter(|_| alarms_config.missed_proposals),
+                    voting_power: monitor.voting_power(validator.addr),
+                    reference_voting_power: monitor.reference_voting_power(validator.addr),
+                    misbehavior: misbehavior
+                        .iter()
+                        .find(|misbehavior| misbehavior.validator == validator.addr)
+                        .cloned(),
+                };
+
+                pager_service
+                    .ready()
+                    .await
+                    .expect("PagerService not ready")
+                    .call(PagerRequest::Event(event))
+                    .await
+                    .expect("PagerService error");
+            }
+
+            if alarms_config.network_evidence {
+                let mut alarms: Vec<PagerAlarm> = vec![];
+
+                for misbehavior in &misbehavior {
+                    let subject = misbehavior.validator.to_string();
+
+                    if validators.iter().any(|v| v.addr == misbehavior.validator)
+                        || alarms.iter().any(|alarm| alarm.subject == subject)
+                    {
+                        continue;
+                    }
+
+                    let message = match monitor.moniker(misbehavior.validator) {
+                        Some(moniker) => {
+                            format!("({moniker}) committed misbehavior: {misbehavior}")
+                        }
+                        None => format!("committed misbehavior: {misbehavior}"),
+                    };
+
+                    alarms.push(PagerAlarm {
+                        chain_id: chain_id.clone(),
+                        kind: AlarmKind::NetworkEvidence,
+                        severity: Severity::Warning,
+                        subject,
+                        message,
+                        details: AlarmDetails {
+                            moniker: monitor.moniker(misbehavior.validator).map(str::to_owned),
+                            height: Some(misbehavior.height),
+                            ..AlarmDetails::default()
+                        },
+                    });
+                }
+
+                pager_service
+                    .ready()
+                    .await
+                    .expect("PagerService not ready")
+                    .call(PagerRequest::Sync {
+                        chain_id: chain_id.clone(),
+                        kind: AlarmKind::NetworkEvidence,
+                        alarms,
+                    })
+                    .await
+                    .expect("PagerService error");
+            }
+        }
+    })
+}
+
+async fn run_consensus_monitor(
+    config: ChainConfig,
+    rpc_urls: Vec<String>,
+    max_round: u32,
+    mut pager_service: PagerBuffer,
+    mut status_service: StatusBuffer,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let validators = config.validators();
+        let client_manager =
+            ClientManager::new(rpc_urls).expect("couldn't initialize RPC client manager");
+        let monitor = ConsensusMonitor::new(config.id, client_manager);
+
+        loop {
+            if let Some(status) = monitor.poll(&validators).await {
+                status_service
+                    .ready()
+                    .await
+                    .expect("StatusService not ready")
+                    .call(StatusRequest::UpdateConsensus(status.clone()))
+                    .await
+                    .expect("StatusService error");
+
+                pager_service
+                    .ready()
+                    .await
+                    .expect("PagerService not ready")
+                    .call(PagerRequest::Consensus(ConsensusEvent {
+                        status,
+                        max_round,
+                    }))
+                    .await
+                    .expect("PagerService error");
+            }
+
+            tokio::time::sleep(consensus_monitor::POLL_INTERVAL).await;
+        }
+    })
+}
+
+async fn run_halt_monitor(
+    config: ChainConfig,
+    alarms_config: AlarmsConfig,
+    mut pager_service: PagerBuffer,
+    mut status_service: StatusBuffer,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let started_at = Time::now();
+        let chain_id = config.id.clone();
+        let mut plans = config.upgrade_plans();
+        let client_manager =
+            ClientManager::new(config.rpc_urls).expect("couldn't initialize RPC client manager");
+
+        for check in 0.. {
+            if config.discover_upgrades && check % UPGRADE_DISCOVERY_CHECKS == 0 {
+                // Discovered plans are retained after they've executed (and are no longer
+                // returned) so the chain resuming can be tracked
+                if let Some(plan) = upgrade::current_plan(&client_manager).await
+                    && !plans.contains(&plan)
+                {
+                    info!("[{chain_id}] discovered planned {plan}");
+                    plans.push(plan);
+                }
+            }
+
+            let response = status_service
+                .ready()
+                .await
+                .expect("StatusService not ready")
+                .call(StatusRequest::GetChain(chain_id.clone()))
+                .await
+                .expect("StatusService error");
+
+            let chain = match response {
+                StatusResponse::Chain(chain) => chain,
+                other => panic!("unexpected StatusService response: {:?}", other),
+            };
+
+            // A chain monitor which never reports a block is measured from startup
+            let height = chain.as_ref().map(|chain| chain.height);
+            let imported_at = chain.map_or(started_at, |chain| chain.imported_at);
+
+            let event = ChainEvent {
+                chain_id: chain_id.clone(),
+                height,
+                halted_for: Time::now().duration_since(imported_at).unwrap_or_default(),
+                halt_after: alarms_config.halt_after(),
+                upgrade: height.and_then(|height| {
+                    plans
+                        .iter()
+                        .find(|plan| plan.covers(height, alarms_config.upgrade_window_blocks))
+                        .cloned()
+                }),
+                upgrade_resume_deadline: alarms_config.upgrade_resume_deadline(),
+            };
 
             pager_service
                 .ready()
                 .await
                 .expect("PagerService not ready")
-                .call(PagerRequest::Event {
-                    chain_id: chain_id.clone(),
-                    missed_blocks,
-                    recent_blocks,
-                })
+                .call(PagerRequest::Chain(event))
+                .await
+                .expect("PagerService error");
+
+            tokio::time::sleep(HALT_CHECK_INTERVAL).await;
+        }
+    })
+}
+
+async fn run_node_monitor(
+    config: NodeConfig,
+    mut pager_service: PagerBuffer,
+    mut status_service: StatusBuffer,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        info!(
+            "[{}] monitoring node {} ({})",
+            config.chain_id, config.name, config.rpc_url
+        );
+
+        let monitor = NodeMonitor::new(config).expect("couldn't initialize node RPC client");
+
+        loop {
+            let response = status_service
+                .ready()
+                .await
+                .expect("StatusService not ready")
+                .call(StatusRequest::BestHeight(monitor.config().chain_id.clone()))
+                .await
+                .expect("StatusService error");
+
+            let best_height = match response {
+                StatusResponse::BestHeight(height) => height,
+                other => panic!("unexpected StatusService response: {:?}", other),
+            };
+
+            let status = monitor.poll(best_height).await;
+
+            status_service
+                .ready()
+                .await
+                .expect("StatusService not ready")
+                .call(StatusRequest::UpdateNode(status.clone()))
+                .await
+                .expect("StatusService error");
+
+            pager_service
+                .ready()
+                .await
+                .expect("PagerService not ready")
+                .call(PagerRequest::Node(NodeEvent {
+                    status,
+                    max_lag: monitor.config().max_lag,
+                    min_peers: monitor.config().min_peers,
+                }))
                 .await
                 .expect("PagerService error");
+
+            tokio::time::sleep(node_monitor::POLL_INTERVAL).await;
         }
     })
 }
@@ -109,8 +569,18 @@ async fn run_monitor(config: ChainConfig, mut pager_service: PagerBuffer) -> Joi
 async fn init_pager_monitor(
     alerting_interval: Duration,
     pager_service: PagerBuffer,
+    pager_notify: Arc<Notify>,
+    dispatcher: Dispatcher,
+    cluster: Cluster,
 ) -> JoinHandle<()> {
-    tokio::spawn(
-        async move { monitor_pager_service(alerting_interval, pager_service.clone()).await },
-    )
+    tokio::spawn(async move {
+        monitor_pager_service(
+            alerting_interval,
+            pager_service.clone(),
+            pager_notify,
+            dispatcher,
+            cluster,
+        )
+        .await
+    })
 }
@@ -0,0 +1,409 @@
+//! `tui` subcommand - run chain monitors with a live terminal dashboard
+
+use super::start::{spawn_monitors, Monitors};
+use crate::{
+    chain_state::Vote,
+    config::ObservatoryConfig,
+    pager::{PagerAlarm, PagerBuffer, PagerRequest, PagerResponse, Severity, CHAIN_SUBJECT},
+    prelude::*,
+    status::{ChainStatus, StatusBuffer, StatusRequest, StatusResponse, ValidatorStatus},
+};
+use abscissa_core::{config, Command, FrameworkError, Runnable};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind};
+use futures::StreamExt;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+use std::{io, time::Duration};
+use tower::{Service, ServiceExt};
+
+/// How often the dashboard is redrawn with fresh data.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `tui` subcommand
+///
+/// Runs the same chain monitors as `start` (without sending pages) and renders
+/// their state as an interactive terminal dashboard.
+#[derive(clap::Parser, Command, Debug)]
+pub struct TuiCmd {}
+
+impl Runnable for TuiCmd {
+    /// Run the terminal dashboard.
+    fn run(&self) {
+        let config = APP.config();
+
+        if config.chains.is_empty() {
+            panic!("no chains configured (no 'observatory.toml'?)");
+        }
+
+        abscissa_tokio::run(&APP, async {
+            let Monitors {
+                pager_service,
+                status_service,
+                ..
+            } = spawn_monitors(&config).await;
+
+            let mut terminal = ratatui::init();
+            let result = Dashboard::new(pager_service, status_service)
+                .run(&mut terminal)
+                .await;
+            ratatui::restore();
+
+            if let Err(err) = result {
+                status_err!("terminal error: {}", err);
+            }
+        })
+        .expect("Tokio runtime crashed");
+    }
+}
+
+impl config::Override<ObservatoryConfig> for TuiCmd {
+    fn override_config(
+        &self,
+        config: ObservatoryConfig,
+    ) -> Result<ObservatoryConfig, FrameworkError> {
+        Ok(config)
+    }
+}
+
+/// Interactive terminal dashboard state.
+struct Dashboard {
+    /// Pager service used to look up alarms.
+    pager_service: PagerBuffer,
+
+    /// Status service used to look up chain state.
+    status_service: StatusBuffer,
+
+    /// Most recently fetched chain statuses.
+    chains: Vec<ChainStatus>,
+
+    /// Most recently fetched alarms.
+    alarms: Vec<PagerAlarm>,
+
+    /// Selected row in the chain table, which has a row per validator on each chain.
+    chain_table: TableState,
+
+    /// Selected row in the block table, if a chain has been opened.
+    block_table: Option<TableState>,
+}
+
+impl Dashboard {
+    fn new(pager_service: PagerBuffer, status_service: StatusBuffer) -> Self {
+        Self {
+            pager_service,
+            status_service,
+            chains: vec![],
+            alarms: vec![],
+            chain_table: TableState::default().with_selected(0),
+            block_table: None,
+        }
+    }
+
+    /// Run the dashboard until the user quits.
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let mut events = EventStream::new();
+
+        loop {
+            self.refresh().await;
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let event = match tokio::time::timeout(REFRESH_INTERVAL, events.next()).await {
+                Ok(Some(event)) => event?,
+                Ok(None) => return Ok(()),
+                Err(_) => continue,
+            };
+
+            if let Event::Key(key) = event {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+
+                match key.code {
+                    KeyCode::Char('q') => return Ok(()),
+                    KeyCode::Esc | KeyCode::Backspace | KeyCode::Left => self.block_table = None,
+                    KeyCode::Enter | KeyCode::Right => {
+                        self.block_table = Some(TableState::default().with_selected(0))
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => self.selected_table().select_previous(),
+                    KeyCode::Down | KeyCode::Char('j') => self.selected_table().select_next(),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// Fetch the latest chain statuses and alarms.
+    async fn refresh(&mut self) {
+        let response = self
+            .status_service
+            .ready()
+            .await
+            .expect("StatusService not ready")
+            .call(StatusRequest::GetChains)
+            .await
+            .expect("StatusService error");
+
+        self.chains = match response {
+            StatusResponse::Chains(chains) => chains,
+            other => panic!("unexpected StatusService response: {:?}", other),
+        };
+
+        let response = self
+            .pager_service
+            .ready()
+            .await
+            .expect("PagerService not ready")
+            .call(PagerRequest::ActiveAlarms)
+            .await
+            .expect("PagerService error");
+
+        self.alarms = match response {
+            PagerResponse::ActiveAlarms(alarms) => alarms,
+            other => panic!("unexpected PagerService response: {:?}", other),
+        };
+    }
+
+    /// Get the table which currently has keyboard focus.
+    fn selected_table(&mut self) -> &mut TableState {
+        self.block_table.as_mut().unwrap_or(&mut self.chain_table)
+    }
+
+    /// Iterate over each validator along with its index and the chain it's on.
+    fn validators(&self) -> impl Iterator<Item = (&ChainStatus, usize, &ValidatorStatus)> {
+        self.chains.iter().flat_map(|chain| {
+            chain
+                .validators
+                .iter()
+                .enumerate()
+                .map(move |(index, validator)| (chain, index, validator))
+        })
+    }
+
+    /// Get the currently selected chain and the index of the selected validator.
+    fn selected(&self) -> Option<(&ChainStatus, usize, &ValidatorStatus)> {
+        self.validators().nth(self.chain_table.selected()?)
+    }
+
+    /// Get the currently selected chain.
+    fn selected_chain(&self) -> Option<&ChainStatus> {
+        self.selected().map(|(chain, _, _)| chain)
+    }
+
+    fn draw(&mut self, frame: &mut Frame<'_>) {
+        let [main, endpoints, help_area] = Layout::vertical([
+            Constraint::Min(5),
+            Constraint::Length(6),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        if self.block_table.is_some() {
+            self.draw_blocks(frame, main);
+        } else {
+            self.draw_chains(frame, main);
+        }
+
+        self.draw_endpoints(frame, endpoints);
+
+        let help = if self.block_table.is_some() {
+            "↑/↓ scroll  esc back  q quit"
+        } else {
+            "↑/↓ select  enter blocks  q quit"
+        };
+        frame.render_widget(
+            Paragraph::new(help).style(Style::new().fg(Color::DarkGray)),
+            help_area,
+        );
+    }
+
+    fn draw_chains(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let header = Row::new([
+            "Chain",
+            "Validator",
+            "Height",
+            "Streak",
+            "Uptime",
+            "Missed",
+            "Nil",
+            "Proposed",
+            "Block time",
+            "Round",
+            "RPC",
+            "Alarm",
+        ])
+        .style(Style::new().add_modifier(Modifier::BOLD));
+
+        let rows = self.validators().map(|(chain, _, validator)| {
+            let alarm = self
+                .alarms
+                .iter()
+                .filter(|alarm| {
+                    alarm.chain_id == chain.chain_id
+                        && (alarm.subject == validator.name || alarm.subject == CHAIN_SUBJECT)
+                })
+                .max_by_key(|alarm| alarm.severity);
+            let healthy_endpoints = chain
+                .rpc_endpoints
+                .iter()
+                .filter(|endpoint| endpoint.healthy)
+                .count();
+
+            Row::new([
+                Cell::from(chain.chain_id.to_string()),
+                Cell::from(validator.name.clone()),
+                Cell::from(chain.height.to_string()),
+                Cell::from(validator.recent_blocks.to_string()),
+                Cell::from(
+                    validator
+                        .uptime
+                        .map(|uptime| format!("{uptime:.1}%"))
+                        .unwrap_or_default(),
+                ),
+                Cell::from(format!(
+                    "{}/{}",
+                    validator.missed_blocks,
+                    chain.blocks.len()
+                )),
+                Cell::from(validator.nil_votes.to_string()),
+                Cell::from(format!(
+                    "{}/{}",
+                    validator.proposed_blocks,
+                    validator.proposed_blocks + validator.missed_proposals
+                )),
+                Cell::from(format!("{:.2}s", chain.consensus_time_ms as f64 / 1000.0)),
+                match &chain.consensus {
+                    Some(consensus) => {
+                        Cell::from(consensus.round.to_string()).style(if consensus.round > 0 {
+                            Style::new().fg(Color::Yellow)
+                        } else {
+                            Style::new()
+                        })
+                    }
+                    None => Cell::from("-"),
+                },
+                Cell::from(format!("{healthy_endpoints}/{}", chain.rpc_endpoints.len())).style(
+                    if healthy_endpoints == chain.rpc_endpoints.len() {
+                        Style::new().fg(Color::Green)
+                    } else {
+                        Style::new().fg(Color::Red)
+                    },
+                ),
+                match alarm {
+                    Some(alarm) => Cell::from(alarm.message.clone()).style(Style::new().fg(
+                        match alarm.severity {
+                            Severity::Critical => Color::Red,
+                            Severity::Warning => Color::Yellow,
+                        },
+                    )),
+                    None => Cell::from("ok").style(Style::new().fg(Color::Green)),
+                },
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(16),
+                Constraint::Min(12),
+                Constraint::Length(12),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(5),
+                Constraint::Length(9),
+                Constraint::Length(10),
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Min(18),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::new().borders(Borders::ALL).title(" observatory "));
+
+        frame.render_stateful_widget(table, area, &mut self.chain_table);
+    }
+
+    fn draw_blocks(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let Some((chain, index, validator)) = self.selected() else {
+            return;
+        };
+
+        let title = format!(" {} {} recent blocks ", chain.chain_id, validator.name);
+        let header = Row::new(["Height", "Hash", "Time", "Signed"])
+            .style(Style::new().add_modifier(Modifier::BOLD));
+
+        let rows = chain.blocks.iter().map(|block| {
+            let signed = match block.votes[index] {
+                Some(Vote::Commit) => Cell::from("yes").style(Style::new().fg(Color::Green)),
+                Some(Vote::Nil) => Cell::from("nil").style(Style::new().fg(Color::Yellow)),
+                Some(Vote::Absent) => Cell::from("MISSED").style(Style::new().fg(Color::Red)),
+                None => Cell::from("-"),
+            };
+
+            Row::new([
+                Cell::from(block.height.to_string()),
+                Cell::from(block.hash.to_string().chars().take(10).collect::<String>()),
+                Cell::from(block.time.to_rfc3339()),
+                signed,
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(32),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::new().borders(Borders::ALL).title(title));
+
+        let block_table = self.block_table.as_mut().expect("block table not open");
+        frame.render_stateful_widget(table, area, block_table);
+    }
+
+    fn draw_endpoints(&self, frame: &mut Frame<'_>, area: Rect) {
+        let lines = self
+            .selected_chain()
+            .map(|chain| {
+                chain
+                    .rpc_endpoints
+                    .iter()
+                    .map(|endpoint| {
+                        let (status, color) = if endpoint.healthy {
+                            ("up  ", Color::Green)
+                        } else {
+                            ("down", Color::Red)
+                        };
+
+                        Line::from(vec![
+                            Span::styled(status, Style::new().fg(color)),
+                            Span::raw(format!(" {}", endpoint.url)),
+                            Span::styled(
+                                endpoint
+                                    .last_error
+                                    .as_ref()
+                                    .filter(|_| !endpoint.healthy)
+                                    .map(|err| format!("  {err}"))
+                                    .unwrap_or_default(),
+                                Style::new().fg(Color::DarkGray),
+                            ),
+                        ])
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let paragraph = Paragraph::new(lines)
+            .block(Block::new().borders(Borders::ALL).title(" RPC endpoints "));
+        frame.render_widget(paragraph, area);
+    }
+}
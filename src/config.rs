@@ -4,8 +4,15 @@
 //! application's configuration file and/or command-line options
 //! for specifying it.
 
+use crate::{
+    consensus_key::resolve_validator_addr,
+    error::Error,
+    pager::{AlarmKind, Severity},
+    upgrade::UpgradePlan,
+};
 use serde::{Deserialize, Serialize};
-use tendermint::{account, chain};
+use std::{collections::BTreeMap as Map, net::SocketAddr, path::PathBuf, time::Duration};
+use tendermint::{account, block, chain, Time};
 
 /// Observatory Configuration
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -15,22 +22,403 @@ pub struct ObservatoryConfig {
     #[serde(rename = "chain")]
     pub chains: Vec<ChainConfig>,
 
+    /// Our own node configurations.
+    #[serde(default, rename = "node", skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<NodeConfig>,
+
     /// Datadog configuration
     pub datadog: Option<DataDogConfig>,
+
+    /// Sinks alarms are sent to
+    #[serde(default, rename = "sink", skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<SinkConfig>,
+
+    /// Rules for routing alarms to sinks, evaluated in order
+    #[serde(default, rename = "route", skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteConfig>,
+
+    /// HTTP API configuration
+    pub http: Option<HttpConfig>,
+
+    /// Health check configuration
+    #[serde(default)]
+    pub health: HealthConfig,
+
+    /// Redundant instance configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<ClusterConfig>,
+
+    /// Outbound heartbeat (dead man's switch) configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heartbeat: Option<HeartbeatConfig>,
+
+    /// Optional alarm configuration
+    #[serde(default)]
+    pub alarms: AlarmsConfig,
+
+    /// Silences (maintenance windows) during which notifications aren't sent
+    #[serde(default, rename = "silence", skip_serializing_if = "Vec::is_empty")]
+    pub silences: Vec<SilenceConfig>,
+
+    /// File silences added at runtime are persisted to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub silences_path: Option<PathBuf>,
 }
 
 /// Chain Configuration
 #[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(deny_unknown_fields)]
+#[serde(try_from = "ChainConfigInput")]
 pub struct ChainConfig {
     /// Chain ID
     pub id: chain::Id,
 
+    /// Bech32 account prefix, e.g. `cosmos`, which bech32 validator addresses must use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bech32_prefix: Option<String>,
+
     /// Validator Addr
-    pub validator_addr: account::Id,
+    ///
+    /// May be given as a hex address, a bech32 `*valcons` address with the chain's
+    /// `bech32_prefix`, or a consensus public key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validator_addr: Option<account::Id>,
+
+    /// Additional named validators to monitor on this chain
+    #[serde(default, rename = "validator", skip_serializing_if = "Vec::is_empty")]
+    pub validators: Vec<ValidatorConfig>,
 
     /// RPC URLs
     pub rpc_urls: Vec<String>,
+
+    /// Tags alarms can be routed by, e.g. `tier = "mainnet"`
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub tags: Map<String, String>,
+
+    /// Block explorer URL included in alarms, which may use template placeholders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+
+    /// Planned upgrades, during which halt and missed block alarms are suppressed
+    #[serde(default, rename = "upgrade", skip_serializing_if = "Vec::is_empty")]
+    pub upgrades: Vec<UpgradeConfig>,
+
+    /// Discover planned upgrades by querying the upgrade module's current plan
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub discover_upgrades: bool,
+}
+
+impl ChainConfig {
+    /// Get all validators to monitor on this chain.
+    ///
+    /// A validator configured via `validator_addr` is named after its address.
+    pub fn validators(&self) -> Vec<ValidatorConfig> {
+        self.validator_addr
+            .map(|addr| ValidatorConfig {
+                name: addr.to_string(),
+                addr,
+            })
+            .into_iter()
+            .chain(self.validators.iter().cloned())
+            .collect()
+    }
+
+    /// Get the configured upgrade plans for this chain.
+    pub fn upgrade_plans(&self) -> Vec<UpgradePlan> {
+        self.upgrades
+            .iter()
+            .map(|upgrade| UpgradePlan {
+                name: upgrade.name.clone(),
+                height: upgrade.height,
+            })
+            .collect()
+    }
+}
+
+/// Upgrade Configuration
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeConfig {
+    /// Name of the upgrade
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Height at which the chain halts for the upgrade
+    pub height: block::Height,
+}
+
+/// Validator Configuration
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorConfig {
+    /// Name used to identify this validator in alarms
+    pub name: String,
+
+    /// Validator Addr
+    ///
+    /// Accepts the same formats as [`ChainConfig::validator_addr`].
+    pub addr: account::Id,
+}
+
+/// Chain Configuration as written in the config file, before validator addresses are resolved
+/// using the chain's bech32 prefix.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChainConfigInput {
+    id: chain::Id,
+    #[serde(default)]
+    bech32_prefix: Option<String>,
+    #[serde(default)]
+    validator_addr: Option<serde_json::Value>,
+    #[serde(default, rename = "validator")]
+    validators: Vec<ValidatorConfigInput>,
+    rpc_urls: Vec<String>,
+    #[serde(default)]
+    tags: Map<String, String>,
+    #[serde(default)]
+    explorer_url: Option<String>,
+    #[serde(default, rename = "upgrade")]
+    upgrades: Vec<UpgradeConfig>,
+    #[serde(default)]
+    discover_upgrades: bool,
+}
+
+/// Validator Configuration as written in the config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ValidatorConfigInput {
+    name: String,
+    addr: serde_json::Value,
+}
+
+impl TryFrom<ChainConfigInput> for ChainConfig {
+    type Error = Error;
+
+    fn try_from(input: ChainConfigInput) -> Result<Self, Error> {
+        let prefix = input.bech32_prefix.as_deref();
+
+        let validator_addr = input
+            .validator_addr
+            .map(|addr| resolve_validator_addr(&addr, prefix))
+            .transpose()?;
+
+        let validators = input
+            .validators
+            .into_iter()
+            .map(|validator| {
+                Ok(ValidatorConfig {
+                    addr: resolve_validator_addr(&validator.addr, prefix)?,
+                    name: validator.name,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Self {
+            id: input.id,
+            bech32_prefix: input.bech32_prefix,
+            validator_addr,
+            validators,
+            rpc_urls: input.rpc_urls,
+            tags: input.tags,
+            explorer_url: input.explorer_url,
+            upgrades: input.upgrades,
+            discover_upgrades: input.discover_upgrades,
+        })
+    }
+}
+
+/// Node Configuration
+///
+/// One of our own nodes (e.g. a sentry or validator) whose sync status and peers are monitored,
+/// separately from the chain-wide `rpc_urls`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeConfig {
+    /// Name used to identify this node in alarms
+    pub name: String,
+
+    /// Chain ID the node serves
+    pub chain_id: chain::Id,
+
+    /// RPC URL of the node
+    pub rpc_url: String,
+
+    /// Number of blocks behind the network's best height after which an alarm is raised
+    #[serde(default = "NodeConfig::default_max_lag")]
+    pub max_lag: u64,
+
+    /// Number of peers below which an alarm is raised
+    #[serde(default = "NodeConfig::default_min_peers")]
+    pub min_peers: u64,
+}
+
+impl NodeConfig {
+    fn default_max_lag() -> u64 {
+        5
+    }
+
+    fn default_min_peers() -> u64 {
+        3
+    }
+}
+
+/// Silence Configuration
+///
+/// Also used for silences added at runtime through the API.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SilenceConfig {
+    /// Chain ID to silence
+    pub chain_id: chain::Id,
+
+    /// Name of the validator (or node) to silence, or every alarm on the chain if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator: Option<String>,
+
+    /// Time the silence starts (RFC 3339), or immediately if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<Time>,
+
+    /// Time the silence ends (RFC 3339)
+    pub end: Time,
+
+    /// Why the alarms are silenced
+    pub reason: String,
+}
+
+/// Sink Configuration
+///
+/// A destination alarms are sent to.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SinkConfig {
+    /// Name routes refer to this sink by
+    pub name: String,
+
+    /// Type of sink
+    #[serde(rename = "type")]
+    pub sink_type: SinkType,
+
+    /// Datadog API key (`datadog` sinks), defaults to the one in `[datadog]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dd_api_key: Option<String>,
+
+    /// Incoming webhook URL (`slack` sinks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+
+    /// Events API v2 integration key (`pagerduty` sinks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing_key: Option<String>,
+
+    /// Maximum rate at which notifications are sent to this sink
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// Template for each alarm's title, defaulting to one for the sink's type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Template for each alarm's body, defaulting to one for the sink's type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+/// Rate Limit Configuration
+///
+/// Alarms which would exceed the limit are suppressed, and summarized in the sink's next
+/// notification.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Maximum number of notifications sent within `per_secs`
+    pub max_notifications: usize,
+
+    /// Length of the window notifications are counted over, in seconds
+    #[serde(default = "RateLimitConfig::default_per_secs")]
+    pub per_secs: u64,
+}
+
+impl RateLimitConfig {
+    /// Get the length of the window notifications are counted over.
+    pub fn per(&self) -> Duration {
+        Duration::from_secs(self.per_secs)
+    }
+
+    fn default_per_secs() -> u64 {
+        60 * 60
+    }
+}
+
+/// Types of sinks.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkType {
+    /// Datadog events, which can be forwarded to PagerDuty with `@pagerduty`
+    Datadog,
+
+    /// Slack incoming webhook
+    Slack,
+
+    /// PagerDuty Events API v2
+    Pagerduty,
+}
+
+/// Route Configuration
+///
+/// Alarms matching every given condition are sent to the route's sinks. Conditions which are
+/// omitted match any alarm.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    /// Chain IDs to match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chain_ids: Vec<chain::Id>,
+
+    /// Alarm kinds to match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kinds: Vec<AlarmKind>,
+
+    /// Severity to match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+
+    /// Chain tags to match, all of which must be equal
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub tags: Map<String, String>,
+
+    /// Names of the sinks to send matching alarms to
+    pub sinks: Vec<String>,
+
+    /// Keep evaluating later routes after this one matches
+    #[serde(
+        default,
+        rename = "continue",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub continue_matching: bool,
+
+    /// Additional sinks to notify when a matching alarm keeps firing
+    #[serde(default, rename = "escalation", skip_serializing_if = "Vec::is_empty")]
+    pub escalations: Vec<EscalationConfig>,
+}
+
+/// Escalation Configuration
+///
+/// Step of a route's escalation policy.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EscalationConfig {
+    /// Number of seconds an alarm must have been firing for before it's escalated
+    pub after_secs: u64,
+
+    /// Names of the sinks to also send the alarm to once it's escalated
+    pub sinks: Vec<String>,
+}
+
+impl EscalationConfig {
+    /// Get the amount of time an alarm must have been firing for before it's escalated.
+    pub fn after(&self) -> Duration {
+        Duration::from_secs(self.after_secs)
+    }
 }
 
 /// Datadog Configuration
@@ -40,3 +428,258 @@ pub struct DataDogConfig {
     /// Datadog API Key
     pub dd_api_key: Option<String>,
 }
+
+/// HTTP API Configuration
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpConfig {
+    /// Address to listen for HTTP requests on
+    pub listen_addr: SocketAddr,
+}
+
+/// Optional Alarm Configuration
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmsConfig {
+    /// Raise a warning when one of our validators misses a scheduled proposal
+    #[serde(default)]
+    pub missed_proposals: bool,
+
+    /// Raise a warning when evidence of misbehavior by any other validator is included in a block
+    #[serde(default)]
+    pub network_evidence: bool,
+
+    /// Consensus round at a single height at or past which a warning is raised
+    #[serde(default = "AlarmsConfig::default_max_round")]
+    pub max_round: u32,
+
+    /// Relative change in a validator's voting power, compared to roughly one history window
+    /// earlier, at or past which a warning is raised
+    #[serde(default = "AlarmsConfig::default_max_voting_power_change")]
+    pub max_voting_power_change: f64,
+
+    /// Seconds without a new block after which a chain is considered halted
+    #[serde(default = "AlarmsConfig::default_halt_after_secs")]
+    pub halt_after_secs: u64,
+
+    /// Number of blocks on either side of a planned upgrade's height during which halt and
+    /// missed block alarms are suppressed
+    #[serde(default = "AlarmsConfig::default_upgrade_window_blocks")]
+    pub upgrade_window_blocks: u64,
+
+    /// Seconds a chain may stay halted for a planned upgrade before paging
+    #[serde(default = "AlarmsConfig::default_upgrade_resume_deadline_secs")]
+    pub upgrade_resume_deadline_secs: u64,
+
+    /// Seconds after which an acknowledgement expires unless it sets its own TTL
+    #[serde(default = "AlarmsConfig::default_ack_ttl_secs")]
+    pub ack_ttl_secs: u64,
+
+    /// Seconds to wait for other alarms after one fires, so they're sent as one notification
+    #[serde(default = "AlarmsConfig::default_group_window_secs")]
+    pub group_window_secs: u64,
+
+    /// Runbook URL included in alarms, which may use template placeholders such as `{kind}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runbook_url: Option<String>,
+}
+
+impl AlarmsConfig {
+    /// Get the amount of time without a new block after which a chain is considered halted.
+    pub fn halt_after(&self) -> Duration {
+        Duration::from_secs(self.halt_after_secs)
+    }
+
+    /// Get the amount of time a chain may stay halted for a planned upgrade.
+    pub fn upgrade_resume_deadline(&self) -> Duration {
+        Duration::from_secs(self.upgrade_resume_deadline_secs)
+    }
+
+    /// Get the amount of time after which an acknowledgement expires by default.
+    pub fn ack_ttl(&self) -> Duration {
+        Duration::from_secs(self.ack_ttl_secs)
+    }
+
+    /// Get the amount of time alarms are grouped over before being sent.
+    pub fn group_window(&self) -> Duration {
+        Duration::from_secs(self.group_window_secs)
+    }
+
+    fn default_max_round() -> u32 {
+        3
+    }
+
+    fn default_max_voting_power_change() -> f64 {
+        0.1
+    }
+
+    fn default_halt_after_secs() -> u64 {
+        120
+    }
+
+    fn default_upgrade_window_blocks() -> u64 {
+        10
+    }
+
+    fn default_upgrade_resume_deadline_secs() -> u64 {
+        1800
+    }
+
+    fn default_ack_ttl_secs() -> u64 {
+        4 * 60 * 60
+    }
+
+    fn default_group_window_secs() -> u64 {
+        30
+    }
+}
+
+impl Default for AlarmsConfig {
+    fn default() -> Self {
+        Self {
+            missed_proposals: false,
+            network_evidence: false,
+            max_round: Self::default_max_round(),
+            max_voting_power_change: Self::default_max_voting_power_change(),
+            halt_after_secs: Self::default_halt_after_secs(),
+            upgrade_window_blocks: Self::default_upgrade_window_blocks(),
+            upgrade_resume_deadline_secs: Self::default_upgrade_resume_deadline_secs(),
+            ack_ttl_secs: Self::default_ack_ttl_secs(),
+            group_window_secs: Self::default_group_window_secs(),
+            runbook_url: None,
+        }
+    }
+}
+
+/// Health Check Configuration
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct HealthConfig {
+    /// Seconds without a heartbeat after which a chain monitor is considered wedged
+    #[serde(default = "HealthConfig::default_liveness_timeout_secs")]
+    pub liveness_timeout_secs: u64,
+
+    /// Multiple of a chain's consensus time without a new block after which it isn't ready
+    #[serde(default = "HealthConfig::default_readiness_multiple")]
+    pub readiness_multiple: f64,
+}
+
+impl HealthConfig {
+    fn default_liveness_timeout_secs() -> u64 {
+        120
+    }
+
+    fn default_readiness_multiple() -> f64 {
+        10.0
+    }
+}
+
+/// Heartbeat Configuration
+///
+/// Heartbeats are sent while every chain monitor is live, so that an external service can alert
+/// when they stop arriving.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeartbeatConfig {
+    /// URL to ping, e.g. a healthchecks.io check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// HTTP method used to ping `url`
+    #[serde(default)]
+    pub method: HeartbeatMethod,
+
+    /// Send an `observatory.heartbeat` metric to Datadog using the `[datadog]` API key
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub datadog: bool,
+
+    /// Seconds between heartbeats
+    #[serde(default = "HeartbeatConfig::default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl HeartbeatConfig {
+    /// Get the amount of time between heartbeats.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+
+    fn default_interval_secs() -> u64 {
+        60
+    }
+}
+
+/// Cluster Configuration
+///
+/// Redundant instances monitor each other over their HTTP APIs, and only the leader (the live
+/// instance with the lowest ID) sends notifications.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClusterConfig {
+    /// Unique ID of this instance, included in the alarms it sends
+    pub instance_id: String,
+
+    /// Other instances
+    #[serde(default, rename = "peer", skip_serializing_if = "Vec::is_empty")]
+    pub peers: Vec<PeerConfig>,
+
+    /// Seconds between polls of each peer
+    #[serde(default = "ClusterConfig::default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+
+    /// Seconds without a successful poll after which a peer is considered failed
+    #[serde(default = "ClusterConfig::default_peer_timeout_secs")]
+    pub peer_timeout_secs: u64,
+}
+
+impl ClusterConfig {
+    /// Get the amount of time between polls of each peer.
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs)
+    }
+
+    /// Get the amount of time without a successful poll after which a peer is considered failed.
+    pub fn peer_timeout(&self) -> Duration {
+        Duration::from_secs(self.peer_timeout_secs)
+    }
+
+    fn default_poll_interval_secs() -> u64 {
+        10
+    }
+
+    fn default_peer_timeout_secs() -> u64 {
+        30
+    }
+}
+
+/// Peer Configuration
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PeerConfig {
+    /// Instance ID of the peer
+    pub id: String,
+
+    /// Base URL of the peer's HTTP API
+    pub url: String,
+}
+
+/// HTTP methods heartbeats can be sent with.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HeartbeatMethod {
+    /// Ping the URL with a GET request
+    #[default]
+    Get,
+
+    /// POST the health report to the URL as JSON
+    Post,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            liveness_timeout_secs: Self::default_liveness_timeout_secs(),
+            readiness_multiple: Self::default_readiness_multiple(),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Validator consensus public keys and addresses.
+//!
+//! Supports the formats printed by `<appd> tendermint show-validator` (Cosmos SDK),
+//! `cometbft show-validator` (CometBFT), bare Base64-encoded keys, and bech32
+//! `*valcons` addresses with the chain's own prefix.
+
+use crate::error::{Error, ErrorKind};
+use std::str::FromStr;
+use subtle_encoding::{base64, bech32};
+use tendermint::{account, PublicKey};
+
+/// Parse a validator's consensus address given in any of the following formats:
+///
+/// - Hex-encoded address, i.e. `account::Id`: `95E060D07713070FE9822F6C50BD76BCCBF9F17A`
+/// - Bech32 consensus address with the chain's prefix, e.g. `cosmosvalcons1...` when
+///   `bech32_prefix` is `cosmos`
+/// - Consensus public key in any format supported by [`parse_consensus_pubkey`]
+pub fn parse_validator_addr(
+    input: &str,
+    bech32_prefix: Option<&str>,
+) -> Result<account::Id, Error> {
+    let input = input.trim();
+
+    if input.len() == account::LENGTH * 2 && input.chars().all(|c| c.is_ascii_hexdigit()) {
+        return account::Id::from_str(input).map_err(|err| invalid_addr(format!("{input}: {err}")));
+    }
+
+    if let Ok((hrp, bytes)) = bech32::decode(input) {
+        let Some(prefix) = bech32_prefix else {
+            return Err(invalid_addr(format!(
+                "{input}: set the chain's bech32_prefix to use bech32 addresses"
+            )));
+        };
+
+        let expected = format!("{prefix}valcons");
+
+        if hrp != expected {
+            return Err(invalid_addr(format!(
+                "{input}: expected '{expected}' prefix, got '{hrp}'"
+            )));
+        }
+
+        return account::Id::try_from(bytes).map_err(|err| invalid_addr(format!("{input}: {err}")));
+    }
+
+    parse_consensus_pubkey(input).map(consensus_address)
+}
+
+/// Resolve a validator address given in the config as a string in any format supported by
+/// [`parse_validator_addr`], or as a TOML table containing a consensus public key
+/// (e.g. `{ "@type" = "/cosmos.crypto.ed25519.PubKey", key = "..." }`).
+pub fn resolve_validator_addr(
+    input: &serde_json::Value,
+    bech32_prefix: Option<&str>,
+) -> Result<account::Id, Error> {
+    match input {
+        serde_json::Value::String(addr) => parse_validator_addr(addr, bech32_prefix),
+        key @ serde_json::Value::Object(_) => {
+            parse_consensus_pubkey(&key.to_string()).map(consensus_address)
+        }
+        _ => Err(invalid_addr(
+            "expected an address or consensus public key".to_owned(),
+        )),
+    }
+}
+
+/// Parse a consensus public key in any of the supported formats:
+///
+/// - Cosmos SDK JSON: `{"@type":"/cosmos.crypto.ed25519.PubKey","key":"<base64>"}`
+/// - CometBFT JSON: `{"type":"tendermint/PubKeyEd25519","value":"<base64>"}`
+/// - Bare Base64: 32-byte keys are Ed25519, 33-byte keys are compressed secp256k1
+pub fn parse_consensus_pubkey(input: &str) -> Result<PublicKey, Error> {
+    let input = input.trim();
+
+    if !input.starts_with('{') {
+        let bytes = decode_base64(input)?;
+
+        return match bytes.len() {
+            32 => PublicKey::from_raw_ed25519(&bytes),
+            33 => PublicKey::from_raw_secp256k1(&bytes),
+            _ => None,
+        }
+        .ok_or_else(|| invalid_key(format!("unexpected key length: {} bytes", bytes.len())));
+    }
+
+    let json = serde_json::from_str::<serde_json::Value>(input)
+        .map_err(|err| invalid_key(format!("malformed JSON: {err}")))?;
+
+    let (key_type, key) = match (
+        json.get("@type").or_else(|| json.get("type")),
+        json.get("key").or_else(|| json.get("value")),
+    ) {
+        (Some(key_type), Some(key)) => (key_type.as_str(), key.as_str()),
+        _ => return Err(invalid_key("missing key type or value".to_owned())),
+    };
+
+    let bytes = decode_base64(key.unwrap_or_default())?;
+
+    match key_type.unwrap_or_default() {
+        "/cosmos.crypto.ed25519.PubKey" | "tendermint/PubKeyEd25519" => {
+            PublicKey::from_raw_ed25519(&bytes)
+        }
+        "/cosmos.crypto.secp256k1.PubKey" | "tendermint/PubKeySecp256k1" => {
+            PublicKey::from_raw_secp256k1(&bytes)
+        }
+        other => return Err(invalid_key(format!("unsupported key type: {other}"))),
+    }
+    .ok_or_else(|| invalid_key("malformed public key".to_owned()))
+}
+
+/// Compute the consensus address (i.e. `account::Id`) for the given public key.
+pub fn consensus_address(public_key: PublicKey) -> account::Id {
+    account::Id::from(public_key)
+}
+
+fn decode_base64(input: &str) -> Result<Vec<u8>, Error> {
+    base64::decode(input).map_err(|err| invalid_key(format!("invalid Base64: {err}")))
+}
+
+fn invalid_addr(message: String) -> Error {
+    ErrorKind::Config
+        .context(format!("invalid validator address: {message}"))
+        .into()
+}
+
+fn invalid_key(message: String) -> Error {
+    ErrorKind::Config
+        .context(format!("invalid consensus public key: {message}"))
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{consensus_address, parse_consensus_pubkey, parse_validator_addr};
+    use crate::config::ObservatoryConfig;
+    use subtle_encoding::bech32;
+
+    const KEY: &str = "/Xme6qdIm0Ou+SnMVZRfWvNeuBGp3szi0ufSMoGVo8E=";
+    const ADDR: &str = "6B7C7EE945EA4D84AE8F2A259BB15F17830E6182";
+
+    #[test]
+    fn parses_all_formats() {
+        for input in [
+            KEY.to_owned(),
+            format!(r#"{{"@type":"/cosmos.crypto.ed25519.PubKey","key":"{KEY}"}}"#),
+            format!(r#"{{"type":"tendermint/PubKeyEd25519","value":"{KEY}"}}"#),
+        ] {
+            let key = parse_consensus_pubkey(&input).unwrap();
+            assert_eq!(consensus_address(key).to_string(), ADDR);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_keys() {
+        assert!(parse_consensus_pubkey("AAAA").is_err());
+        assert!(parse_consensus_pubkey(r#"{"@type":"/foo","key":"AAAA"}"#).is_err());
+        assert!(parse_consensus_pubkey("{").is_err());
+    }
+
+    #[test]
+    fn parses_validator_addrs() {
+        let addr = parse_validator_addr(ADDR, None).unwrap();
+        assert_eq!(
+            parse_validator_addr(&ADDR.to_lowercase(), None).unwrap(),
+            addr
+        );
+        assert_eq!(parse_validator_addr(KEY, None).unwrap(), addr);
+
+        for prefix in ["cosmos", "osmo"] {
+            let valcons = bech32::encode(format!("{prefix}valcons"), addr.as_bytes());
+            assert_eq!(parse_validator_addr(&valcons, Some(prefix)).unwrap(), addr);
+        }
+
+        let valcons = bech32::encode("osmovalcons", addr.as_bytes());
+        assert!(parse_validator_addr(&valcons, Some("cosmos")).is_err());
+        assert!(parse_validator_addr(&valcons, None).is_err());
+
+        let valoper = bech32::encode("cosmosvaloper", addr.as_bytes());
+        assert!(parse_validator_addr(&valoper, Some("cosmos")).is_err());
+    }
+
+    #[test]
+    fn normalizes_validator_addrs() {
+        let config: ObservatoryConfig = toml::from_str(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            bech32_prefix = "cosmos"
+            validator_addr = "cosmosvalcons1dd78a629afxcft509gjehv2lz7psucvz8np8ex"
+            rpc_urls = []
+
+            [[chain]]
+            id = "osmosis-1"
+            validator_addr = { "@type" = "/cosmos.crypto.ed25519.PubKey", key = "/Xme6qdIm0Ou+SnMVZRfWvNeuBGp3szi0ufSMoGVo8E=" }
+            rpc_urls = []
+
+            [[chain.validator]]
+            name = "backup"
+            addr = "6B7C7EE945EA4D84AE8F2A259BB15F17830E6182"
+            "#,
+        )
+        .unwrap();
+
+        for validator in config.chains.iter().flat_map(|chain| chain.validators()) {
+            assert_eq!(validator.addr.to_string(), ADDR);
+        }
+
+        let mismatched = toml::from_str::<ObservatoryConfig>(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            bech32_prefix = "osmo"
+            validator_addr = "cosmosvalcons1dd78a629afxcft509gjehv2lz7psucvz8np8ex"
+            rpc_urls = []
+            "#,
+        );
+        assert!(mismatched.is_err());
+    }
+}
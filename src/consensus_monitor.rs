@@ -0,0 +1,236 @@
+//! Consensus monitor: polls `/consensus_state` to track the current height, round and step
+//! of each chain, giving early warning when rounds escalate before the chain halts outright.
+
+use crate::{client_manager::ClientManager, config::ValidatorConfig};
+use serde::Serialize;
+use std::time::Duration;
+use tendermint::{account, block, chain, Time};
+use tendermint_rpc::{
+    endpoint::consensus_state::{RoundState, RoundVote, RoundVotes},
+    Client,
+};
+
+/// How often the consensus state is polled.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Consensus monitor
+#[derive(Debug)]
+pub struct ConsensusMonitor {
+    /// Chain ID being monitored.
+    chain_id: chain::Id,
+
+    /// RPC clients for the chain's endpoints and our own nodes.
+    client_manager: ClientManager,
+}
+
+impl ConsensusMonitor {
+    /// Create a new consensus monitor.
+    pub fn new(chain_id: chain::Id, client_manager: ClientManager) -> Self {
+        Self {
+            chain_id,
+            client_manager,
+        }
+    }
+
+    /// Poll all endpoints, returning the most advanced consensus state reported by any of them.
+    pub async fn poll(&self, validators: &[ValidatorConfig]) -> Option<ConsensusStatus> {
+        self.client_manager
+            .request(|client| client.consensus_state())
+            .await
+            .into_iter()
+            .filter_map(|result| result.ok())
+            .map(|response| response.round_state)
+            .max_by_key(|state| {
+                (
+                    state.height_round_step.height,
+                    state.height_round_step.round,
+                    state.height_round_step.step,
+                )
+            })
+            .map(|state| ConsensusStatus::new(self.chain_id.clone(), &state, validators))
+    }
+}
+
+/// Snapshot of a chain's consensus state.
+#[derive(Clone, Debug, Serialize)]
+pub struct ConsensusStatus {
+    /// Chain ID.
+    pub chain_id: chain::Id,
+
+    /// Height being decided.
+    pub height: block::Height,
+
+    /// Current round at this height.
+    pub round: u32,
+
+    /// Current step within the round.
+    pub step: &'static str,
+
+    /// Fraction of voting power which prevoted in the current round.
+    pub prevotes: Option<f64>,
+
+    /// Fraction of voting power which precommitted in the current round.
+    pub precommits: Option<f64>,
+
+    /// Votes cast by each of our validators in the current round.
+    pub validators: Vec<ValidatorRoundVotes>,
+
+    /// Time the current height started.
+    pub started_at: Time,
+
+    /// Time this snapshot was taken.
+    pub updated_at: Time,
+}
+
+impl ConsensusStatus {
+    /// Summarize the given round state.
+    pub fn new(chain_id: chain::Id, state: &RoundState, validators: &[ValidatorConfig]) -> Self {
+        let round = state.height_round_step.round.value();
+        let votes = state
+            .height_vote_set
+            .iter()
+            .find(|votes| votes.round == round);
+
+        Self {
+            chain_id,
+            height: state.height_round_step.height,
+            round,
+            step: step_name(state.height_round_step.step),
+            prevotes: votes.and_then(|votes| bit_array_fraction(&votes.prevotes_bit_array)),
+            precommits: votes.and_then(|votes| bit_array_fraction(&votes.precommits_bit_array)),
+            validators: validators
+                .iter()
+                .map(|validator| ValidatorRoundVotes::new(validator, votes))
+                .collect(),
+            started_at: state.start_time,
+            updated_at: Time::now(),
+        }
+    }
+}
+
+/// Votes cast by one of our validators in the current round.
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorRoundVotes {
+    /// Name of the validator.
+    pub name: String,
+
+    /// Prevote received from the validator.
+    pub prevote: RoundVoteStatus,
+
+    /// Precommit received from the validator.
+    pub precommit: RoundVoteStatus,
+}
+
+impl ValidatorRoundVotes {
+    fn new(validator: &ValidatorConfig, votes: Option<&RoundVotes>) -> Self {
+        Self {
+            name: validator.name.clone(),
+            prevote: votes
+                .map(|votes| RoundVoteStatus::find(&votes.prevotes, validator.addr))
+                .unwrap_or(RoundVoteStatus::Missing),
+            precommit: votes
+                .map(|votes| RoundVoteStatus::find(&votes.precommits, validator.addr))
+                .unwrap_or(RoundVoteStatus::Missing),
+        }
+    }
+}
+
+/// Whether a validator's vote has been received in a round.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundVoteStatus {
+    /// Voted for a block.
+    Block,
+
+    /// Voted nil.
+    Nil,
+
+    /// No vote received (yet).
+    Missing,
+}
+
+impl RoundVoteStatus {
+    /// Find the given validator's vote, matching on the address fingerprint (its first 6 bytes).
+    fn find(votes: &[RoundVote], validator_address: account::Id) -> Self {
+        votes
+            .iter()
+            .find_map(|vote| match vote {
+                RoundVote::Vote(summary)
+                    if validator_address
+                        .as_bytes()
+                        .starts_with(summary.validator_address_fingerprint.as_ref()) =>
+                {
+                    Some(summary)
+                }
+                _ => None,
+            })
+            .map(|summary| {
+                if summary
+                    .block_id_hash_fingerprint
+                    .as_ref()
+                    .iter()
+                    .all(|byte| *byte == 0)
+                {
+                    RoundVoteStatus::Nil
+                } else {
+                    RoundVoteStatus::Block
+                }
+            })
+            .unwrap_or(RoundVoteStatus::Missing)
+    }
+}
+
+/// Get the name of a consensus step.
+fn step_name(step: i8) -> &'static str {
+    match step {
+        1 => "new_height",
+        2 => "new_round",
+        3 => "propose",
+        4 => "prevote",
+        5 => "prevote_wait",
+        6 => "precommit",
+        7 => "precommit_wait",
+        8 => "commit",
+        _ => "unknown",
+    }
+}
+
+/// Parse the fraction of voting power from a vote bit array, e.g. `BA{4:xx_x} 0.75`.
+fn bit_array_fraction(bit_array: &str) -> Option<f64> {
+    bit_array.split_whitespace().last()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bit_array_fraction, RoundVoteStatus};
+    use std::str::FromStr;
+    use tendermint::account;
+    use tendermint_rpc::endpoint::consensus_state::{RoundVote, VoteSummary};
+
+    #[test]
+    fn finds_validator_votes() {
+        let vote = |s: &str| RoundVote::Vote(VoteSummary::from_str(s).unwrap());
+        let votes = [
+            RoundVote::Nil,
+            vote("Vote{1:000001E443FD 100/02/1(Prevote) 634ADAF1F402 7BB974E1BA40 @ 2019-08-01T11:52:35.513572509Z}"),
+            vote("Vote{2:2DA21E474F57 100/02/1(Prevote) 000000000000 2987C33E8F87 @ 2021-03-25T12:12:03.693870115Z}"),
+        ];
+
+        let addr = |s: &str| account::Id::from_str(s).unwrap();
+        let find = |s: &str| RoundVoteStatus::find(&votes, addr(s));
+
+        assert_eq!(
+            find("000001E443FD0000000000000000000000000000"),
+            RoundVoteStatus::Block
+        );
+        assert_eq!(
+            find("2DA21E474F570000000000000000000000000000"),
+            RoundVoteStatus::Nil
+        );
+        assert_eq!(
+            find("6B7C7EE945EA4D84AE8F2A259BB15F17830E6182"),
+            RoundVoteStatus::Missing
+        );
+        assert_eq!(bit_array_fraction("BA{4:xx_x} 0.75"), Some(0.75));
+    }
+}
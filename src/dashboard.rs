@@ -0,0 +1,60 @@
+//! Embedded web dashboard showing a live signing heatmap for each chain.
+//!
+//! The dashboard is a single static page served at `/` which subscribes to
+//! `/api/events`, a server-sent events stream of [`DashboardUpdate`]s.
+
+use crate::{
+    pager::PagerAlarm,
+    status::{BlockStatus, ChainStatus},
+};
+use serde::Serialize;
+use std::time::Duration;
+
+/// HTML for the dashboard page.
+pub const INDEX_HTML: &str = include_str!("dashboard/index.html");
+
+/// Interval at which updates are pushed to connected dashboards.
+pub const UPDATE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Update pushed to the dashboard.
+#[derive(Serialize)]
+pub struct DashboardUpdate<'a> {
+    /// State of each chain.
+    pub chains: Vec<DashboardChain<'a>>,
+}
+
+impl<'a> DashboardUpdate<'a> {
+    /// Build a dashboard update from the current chain statuses and alarms.
+    pub fn new(chains: &'a [ChainStatus], alarms: &'a [PagerAlarm]) -> Self {
+        Self {
+            chains: chains
+                .iter()
+                .map(|status| DashboardChain {
+                    status,
+                    blocks: &status.blocks,
+                    alarms: alarms
+                        .iter()
+                        .filter(|alarm| alarm.chain_id == status.chain_id)
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Serialize this update as a server-sent event.
+    pub fn to_event(&self) -> String {
+        let data = serde_json::to_string(self).expect("couldn't serialize dashboard update");
+        format!("data: {data}\n\n")
+    }
+}
+
+/// State of a chain as shown on the dashboard.
+#[derive(Serialize)]
+pub struct DashboardChain<'a> {
+    #[serde(flatten)]
+    status: &'a ChainStatus,
+
+    blocks: &'a [BlockStatus],
+
+    alarms: Vec<&'a PagerAlarm>,
+}
@@ -0,0 +1,359 @@
+//! Alarm dispatch, between the pager and the sinks.
+//!
+//! Alarms routed to a sink are held until the group window after the first of them has elapsed,
+//! then sent together as a single digest, in which an alarm re-sent during the window appears once
+//! with its latest details. Sinks may also be rate limited: digests which would exceed the limit
+//! are dropped, and the number of distinct alarms they contained is summarized in the sink's next
+//! digest.
+
+use crate::{
+    pager::{Notification, PagerAlarm},
+    routing::Router,
+    sink::Digest,
+    template::Links,
+};
+use std::{
+    collections::{BTreeMap as Map, VecDeque},
+    mem,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// Alarm dispatcher
+#[derive(Debug)]
+pub struct Dispatcher {
+    /// Router deciding which sinks each alarm is sent to.
+    router: Router,
+
+    /// Links included in alarms.
+    links: Links,
+
+    /// Amount of time alarms are grouped over before being sent.
+    group_window: Duration,
+
+    /// Alarms waiting to be sent to each sink, by sink name.
+    outboxes: Map<String, Outbox>,
+}
+
+/// Alarms waiting to be sent to a sink.
+#[derive(Debug, Default)]
+struct Outbox {
+    /// Alarms grouped so far, and the alarms suppressed since the last digest.
+    digest: Digest,
+
+    /// Time the grouped alarms are due to be sent.
+    deadline: Option<Instant>,
+
+    /// Times digests were sent within the sink's rate limit window.
+    sent: VecDeque<Instant>,
+}
+
+impl Dispatcher {
+    /// Create a dispatcher which groups alarms over `group_window`.
+    pub fn new(router: Router, links: Links, group_window: Duration) -> Self {
+        Self {
+            router,
+            links,
+            group_window,
+            outboxes: Map::new(),
+        }
+    }
+
+    /// Get the router alarms are dispatched with.
+    pub fn router(&self) -> &Router {
+        &self.router
+    }
+
+    /// Queue an alarm for each sink it's routed to, including sinks of any escalation steps it
+    /// has been firing long enough to reach.
+    pub fn enqueue(&mut self, notification: Notification, now: Instant) {
+        let Notification { alarm, firing_for } = notification;
+        warn!("[{}] {}: {}", alarm.chain_id, alarm.subject, alarm.message);
+
+        let sinks = self.router.route(&alarm, firing_for);
+
+        if sinks.is_empty() && self.router.has_sinks() {
+            warn!("alarm didn't match any route: {alarm}");
+        }
+
+        for sink in sinks {
+            let outbox = self.outboxes.entry(sink.name().to_owned()).or_default();
+            outbox.deadline.get_or_insert(now + self.group_window);
+
+            // An alarm re-notified (or escalated) within the window replaces its earlier copy
+            let key = alarm.key();
+
+            match outbox
+                .digest
+                .alarms
+                .iter_mut()
+                .find(|queued| queued.key() == key)
+            {
+                Some(queued) => *queued = alarm.clone(),
+                None => outbox.digest.alarms.push(alarm.clone()),
+            }
+
+            outbox
+                .digest
+                .resolved
+                .retain(|resolved| resolved.key() != key);
+        }
+    }
+
+    /// Queue the resolution of a reported alarm for every sink it could have been routed to,
+    /// including all of its escalation steps. Resolutions are sent with the next digest, or on
+    /// their own if none is waiting, and aren't rate limited.
+    pub fn resolve(&mut self, alarm: PagerAlarm) {
+        debug!("alarm resolved: {alarm}");
+
+        for sink in self.router.route(&alarm, Duration::MAX) {
+            let outbox = self.outboxes.entry(sink.name().to_owned()).or_default();
+            let key = alarm.key();
+            outbox
+                .digest
+                .resolved
+                .retain(|resolved| resolved.key() != key);
+            outbox.digest.resolved.push(alarm.clone());
+        }
+    }
+
+    /// Send every digest which is due, as observed by the given instance.
+    pub async fn flush(&mut self, instance: &str) {
+        for (index, digest) in self.take_due(Instant::now()) {
+            let sink = &self.router.sinks()[index];
+
+            match sink.send(&digest, instance, &self.links).await {
+                Ok(()) => debug!("{} alarm(s) sent to {}", digest.alarms.len(), sink.name()),
+                Err(err) => warn!("unable to send alarms to {}: {err}", sink.name()),
+            }
+        }
+    }
+
+    /// Get the amount of time until the next digest is due, if any are waiting.
+    pub fn next_flush(&self, now: Instant) -> Option<Duration> {
+        self.router
+            .sinks()
+            .iter()
+            .filter_map(|sink| {
+                let outbox = self.outboxes.get(sink.name())?;
+
+                match outbox.deadline {
+                    Some(deadline) => Some(deadline.saturating_duration_since(now)),
+                    // Resolutions aren't rate limited
+                    None if !outbox.digest.resolved.is_empty() => Some(Duration::ZERO),
+                    // Summary of suppressed alarms, sent once the oldest digest leaves the window
+                    None if !outbox.digest.suppressed.is_empty() => {
+                        let oldest = outbox.sent.front()?;
+                        Some((*oldest + sink.rate_limit()?.per()).saturating_duration_since(now))
+                    }
+                    None => None,
+                }
+            })
+            .min()
+    }
+
+    /// Take the digests which are due to be sent, along with the index of their sink. Digests
+    /// which would exceed their sink's rate limit are suppressed instead.
+    fn take_due(&mut self, now: Instant) -> Vec<(usize, Digest)> {
+        let mut due = Vec::new();
+
+        for (index, sink) in self.router.sinks().iter().enumerate() {
+            let Some(outbox) = self.outboxes.get_mut(sink.name()) else {
+                continue;
+            };
+
+            let waiting = match outbox.deadline {
+                Some(deadline) => deadline > now,
+                None => outbox.digest.suppressed.is_empty() && outbox.digest.resolved.is_empty(),
+            };
+
+            if waiting {
+                continue;
+            }
+
+            outbox.deadline = None;
+            let notifying =
+                !outbox.digest.alarms.is_empty() || !outbox.digest.suppressed.is_empty();
+
+            if let Some(limit) = sink.rate_limit()
+                && notifying
+            {
+                outbox
+                    .sent
+                    .retain(|sent| now.duration_since(*sent) < limit.per());
+
+                if outbox.sent.len() >= limit.max_notifications {
+                    let alarms = mem::take(&mut outbox.digest.alarms);
+
+                    if !alarms.is_empty() {
+                        warn!(
+                            "rate limit reached for {}; suppressing {} alarm(s)",
+                            sink.name(),
+                            alarms.len()
+                        );
+                    }
+
+                    outbox
+                        .digest
+                        .suppressed
+                        .extend(alarms.iter().map(PagerAlarm::key));
+
+                    let resolved = mem::take(&mut outbox.digest.resolved);
+
+                    if !resolved.is_empty() {
+                        due.push((
+                            index,
+                            Digest {
+                                resolved,
+                                ..Digest::default()
+                            },
+                        ));
+                    }
+
+                    continue;
+                }
+
+                outbox.sent.push_back(now);
+            }
+
+            due.push((index, mem::take(&mut outbox.digest)));
+        }
+
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dispatcher;
+    use crate::{
+        config::ObservatoryConfig,
+        pager::{AlarmDetails, AlarmKind, Notification, PagerAlarm, Severity},
+        routing::Router,
+        template::Links,
+    };
+    use std::time::{Duration, Instant};
+    use tendermint::chain;
+
+    fn notification(chain_id: &str, subject: &str) -> Notification {
+        Notification {
+            alarm: PagerAlarm {
+                chain_id: chain::Id::try_from(chain_id).unwrap(),
+                kind: AlarmKind::MissedBlocks,
+                severity: Severity::Critical,
+                subject: subject.to_owned(),
+                message: "missed blocks".to_owned(),
+                details: AlarmDetails::default(),
+            },
+            firing_for: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn groups_and_rate_limits_alarms() {
+        let config: ObservatoryConfig = toml::from_str(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            rpc_urls = []
+
+            [[sink]]
+            name = "slack"
+            type = "slack"
+            webhook_url = "https://hooks.slack.com/services/x"
+            rate_limit = { max_notifications = 1, per_secs = 3600 }
+            "#,
+        )
+        .unwrap();
+
+        let mut dispatcher = Dispatcher::new(
+            Router::new(&config).unwrap(),
+            Links::default(),
+            Duration::from_secs(30),
+        );
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let mut renotified = notification("cosmoshub-4", "main");
+        renotified.alarm.message = "missed more blocks".to_owned();
+
+        dispatcher.enqueue(notification("cosmoshub-4", "main"), at(0));
+        dispatcher.enqueue(notification("cosmoshub-4", "backup"), at(10));
+        dispatcher.enqueue(renotified, at(15));
+        assert!(dispatcher.take_due(at(20)).is_empty());
+        assert_eq!(dispatcher.next_flush(at(20)), Some(Duration::from_secs(10)));
+
+        let due = dispatcher.take_due(at(30));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1.alarms.len(), 2);
+        assert_eq!(due[0].1.alarms[0].message, "missed more blocks");
+        assert_eq!(dispatcher.next_flush(at(30)), None);
+
+        // Re-sent alarms are only counted once
+        dispatcher.enqueue(notification("cosmoshub-4", "main"), at(60));
+        assert!(dispatcher.take_due(at(90)).is_empty());
+        dispatcher.enqueue(notification("cosmoshub-4", "main"), at(100));
+        assert!(dispatcher.take_due(at(130)).is_empty());
+        assert_eq!(
+            dispatcher.next_flush(at(90)),
+            Some(Duration::from_secs(3600 - 60))
+        );
+
+        let due = dispatcher.take_due(at(3630));
+        assert_eq!(due.len(), 1);
+        assert!(due[0].1.alarms.is_empty());
+        assert_eq!(
+            due[0].1.suppressed_summary().unwrap(),
+            "1 alarm(s) suppressed by rate limit (cosmoshub-4: 1)"
+        );
+    }
+
+    #[test]
+    fn sends_resolutions_after_their_alarms() {
+        let config: ObservatoryConfig = toml::from_str(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            rpc_urls = []
+
+            [[sink]]
+            name = "pagerduty"
+            type = "pagerduty"
+            routing_key = "key"
+            rate_limit = { max_notifications = 1, per_secs = 3600 }
+            "#,
+        )
+        .unwrap();
+
+        let mut dispatcher = Dispatcher::new(
+            Router::new(&config).unwrap(),
+            Links::default(),
+            Duration::from_secs(30),
+        );
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // Resolutions wait for the digest with their alarm, so they're sent after it
+        dispatcher.enqueue(notification("cosmoshub-4", "main"), at(0));
+        dispatcher.resolve(notification("cosmoshub-4", "main").alarm);
+        assert!(dispatcher.take_due(at(10)).is_empty());
+        let due = dispatcher.take_due(at(30));
+        assert_eq!(due[0].1.alarms.len(), 1);
+        assert_eq!(due[0].1.resolved.len(), 1);
+
+        // Otherwise they're sent straight away, even when rate limited
+        dispatcher.resolve(notification("cosmoshub-4", "backup").alarm);
+        assert_eq!(dispatcher.next_flush(at(40)), Some(Duration::ZERO));
+        let due = dispatcher.take_due(at(40));
+        assert!(due[0].1.alarms.is_empty());
+        assert_eq!(due[0].1.resolved[0].subject, "backup");
+
+        // An alarm firing again before its resolution is sent isn't resolved
+        dispatcher.resolve(notification("cosmoshub-4", "main").alarm);
+        dispatcher.enqueue(notification("cosmoshub-4", "main"), at(50));
+        assert!(dispatcher.take_due(at(80)).is_empty());
+        let due = dispatcher.take_due(at(3630));
+        assert_eq!(due[0].1.suppressed.len(), 1);
+        assert!(due[0].1.resolved.is_empty());
+    }
+}
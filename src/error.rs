@@ -15,6 +15,10 @@ pub enum ErrorKind {
     #[error("config error")]
     Config,
 
+    /// Error making an HTTP request
+    #[error("HTTP error")]
+    Http,
+
     /// Input/output error
     #[error("I/O error")]
     Io,
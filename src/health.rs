@@ -0,0 +1,194 @@
+//! Liveness and readiness checks for chain monitors.
+
+use crate::{config::HealthConfig, status::ChainStatus};
+use serde::Serialize;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tendermint::{chain, Time};
+
+/// Heartbeat periodically updated by a chain monitor to indicate it's making progress.
+#[derive(Clone, Debug)]
+pub struct Heartbeat(Arc<Mutex<Instant>>);
+
+impl Heartbeat {
+    /// Create a new heartbeat which has just beaten.
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    /// Record that the monitor is still making progress.
+    pub fn beat(&self) {
+        *self.0.lock().expect("heartbeat lock poisoned") = Instant::now();
+    }
+
+    /// Amount of time since the last heartbeat.
+    pub fn elapsed(&self) -> Duration {
+        self.0.lock().expect("heartbeat lock poisoned").elapsed()
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Health of all chain monitors.
+#[derive(Clone, Debug, Serialize)]
+pub struct HealthReport {
+    /// Are all chain monitors still running?
+    pub live: bool,
+
+    /// Have all chain monitors imported a block recently?
+    pub ready: bool,
+
+    /// Health of each individual chain monitor.
+    pub chains: Vec<ChainHealth>,
+}
+
+impl HealthReport {
+    /// Evaluate the health of the given chains.
+    pub fn new<'a>(
+        config: &HealthConfig,
+        chains: impl IntoIterator<
+            Item = (
+                &'a chain::Id,
+                Option<&'a Heartbeat>,
+                Option<&'a ChainStatus>,
+            ),
+        >,
+    ) -> Self {
+        let chains = chains
+            .into_iter()
+            .map(|(chain_id, heartbeat, status)| {
+                ChainHealth::new(config, chain_id, heartbeat, status)
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            live: chains.iter().all(|chain| chain.live),
+            ready: chains.iter().all(|chain| chain.ready),
+            chains,
+        }
+    }
+}
+
+/// Health of an individual chain monitor.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChainHealth {
+    /// Chain ID.
+    pub chain_id: chain::Id,
+
+    /// Is the monitor still running?
+    pub live: bool,
+
+    /// Has the monitor imported a block recently?
+    pub ready: bool,
+
+    /// Seconds since the monitor's last heartbeat.
+    pub heartbeat_age_secs: Option<u64>,
+
+    /// Seconds since the monitor last imported a block.
+    pub block_age_secs: Option<u64>,
+}
+
+impl ChainHealth {
+    /// Evaluate the health of a single chain monitor.
+    fn new(
+        config: &HealthConfig,
+        chain_id: &chain::Id,
+        heartbeat: Option<&Heartbeat>,
+        status: Option<&ChainStatus>,
+    ) -> Self {
+        let heartbeat_age = heartbeat.map(Heartbeat::elapsed);
+        let live = heartbeat_age
+            .map(|age| age <= Duration::from_secs(config.liveness_timeout_secs))
+            .unwrap_or(false);
+
+        let block_age = status.map(|status| {
+            Time::now()
+                .duration_since(status.imported_at)
+                .unwrap_or(Duration::ZERO)
+        });
+
+        let ready = match (status, block_age) {
+            (Some(status), Some(block_age)) => {
+                let consensus_time = Duration::from_millis(status.consensus_time_ms);
+                live && block_age <= consensus_time.mul_f64(config.readiness_multiple)
+            }
+            _ => false,
+        };
+
+        Self {
+            chain_id: chain_id.clone(),
+            live,
+            ready,
+            heartbeat_age_secs: heartbeat_age.map(|age| age.as_secs()),
+            block_age_secs: block_age.map(|age| age.as_secs()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HealthReport, Heartbeat};
+    use crate::{config::HealthConfig, status::ChainStatus};
+    use std::time::Duration;
+    use tendermint::{block, chain, Time};
+
+    fn chain_status(chain_id: &chain::Id, imported_secs_ago: u64) -> ChainStatus {
+        let imported_at = (Time::now() - Duration::from_secs(imported_secs_ago)).unwrap();
+
+        ChainStatus {
+            chain_id: chain_id.clone(),
+            height: block::Height::from(1u32),
+            consensus_time_ms: 1_000,
+            validators: vec![],
+            rpc_endpoints: vec![],
+            imported_at,
+            updated_at: imported_at,
+            blocks: vec![],
+            signing: None,
+            consensus: None,
+        }
+    }
+
+    #[test]
+    fn ready_when_recently_imported() {
+        let chain_id = chain::Id::try_from("test-1").unwrap();
+        let heartbeat = Heartbeat::new();
+        let status = chain_status(&chain_id, 0);
+        let report = HealthReport::new(
+            &HealthConfig::default(),
+            [(&chain_id, Some(&heartbeat), Some(&status))],
+        );
+
+        assert!(report.live);
+        assert!(report.ready);
+    }
+
+    #[test]
+    fn not_ready_when_block_is_stale() {
+        let chain_id = chain::Id::try_from("test-1").unwrap();
+        let heartbeat = Heartbeat::new();
+        let status = chain_status(&chain_id, 60);
+        let report = HealthReport::new(
+            &HealthConfig::default(),
+            [(&chain_id, Some(&heartbeat), Some(&status))],
+        );
+
+        assert!(report.live);
+        assert!(!report.ready);
+    }
+
+    #[test]
+    fn not_live_without_heartbeat() {
+        let chain_id = chain::Id::try_from("test-1").unwrap();
+        let report = HealthReport::new(&HealthConfig::default(), [(&chain_id, None, None)]);
+
+        assert!(!report.live);
+        assert!(!report.ready);
+    }
+}
@@ -0,0 +1,123 @@
+//! Outbound heartbeat acting as a dead man's switch: while every chain monitor is live, a
+//! heartbeat is periodically sent to a healthchecks.io-style URL and/or Datadog, so an external
+//! service can alert when they stop arriving because observatory died or lost its network.
+
+use crate::{
+    config::{HeartbeatConfig, HeartbeatMethod},
+    error::{Error, ErrorKind},
+    health::HealthReport,
+    http,
+    status::{StatusBuffer, StatusRequest, StatusResponse},
+};
+use hyper::Method;
+use serde_json::json;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tower::{Service, ServiceExt};
+use tracing::{debug, warn};
+
+/// Datadog metric submission endpoint.
+const DATADOG_SERIES_URL: &str = "https://api.datadoghq.com/api/v1/series";
+
+/// Name of the metric sent to Datadog.
+const DATADOG_METRIC: &str = "observatory.heartbeat";
+
+/// Check heartbeats can be sent to every configured target.
+pub fn check(config: &HeartbeatConfig, dd_api_key: Option<&str>) -> Result<(), Error> {
+    if config.url.is_none() && !config.datadog {
+        return Err(ErrorKind::Config
+            .context("no url configured and datadog isn't enabled")
+            .into());
+    }
+
+    if config.datadog && dd_api_key.is_none() {
+        return Err(ErrorKind::Config
+            .context("datadog is enabled but no [datadog] dd_api_key is configured")
+            .into());
+    }
+
+    Ok(())
+}
+
+/// Send heartbeats every `interval_secs` for as long as every chain monitor is live.
+///
+/// Use [`check`] first, as targets which can't be used are skipped.
+pub async fn run(
+    config: HeartbeatConfig,
+    dd_api_key: Option<String>,
+    mut status_service: StatusBuffer,
+) {
+    loop {
+        let response = status_service
+            .ready()
+            .await
+            .expect("StatusService not ready")
+            .call(StatusRequest::GetHealth)
+            .await
+            .expect("StatusService error");
+
+        let health = match response {
+            StatusResponse::Health(health) => health,
+            other => panic!("unexpected StatusService response: {:?}", other),
+        };
+
+        if health.live {
+            if let Some(url) = &config.url {
+                match ping(url, config.method, &health).await {
+                    Ok(()) => debug!("heartbeat sent to {url}"),
+                    Err(err) => warn!("unable to send heartbeat to {url}: {err}"),
+                }
+            }
+
+            if let (true, Some(dd_api_key)) = (config.datadog, &dd_api_key) {
+                match send_datadog_metric(dd_api_key).await {
+                    Ok(()) => debug!("heartbeat sent to Datadog"),
+                    Err(err) => warn!("unable to send heartbeat to Datadog: {err}"),
+                }
+            }
+        } else {
+            warn!("not sending heartbeat: a chain monitor has stopped polling");
+        }
+
+        tokio::time::sleep(config.interval()).await;
+    }
+}
+
+/// Ping the heartbeat URL, POSTing the health report if configured to.
+async fn ping(url: &str, method: HeartbeatMethod, health: &HealthReport) -> Result<(), Error> {
+    let result = match method {
+        HeartbeatMethod::Get => http::request(Method::GET, url, &[], None).await,
+        HeartbeatMethod::Post => {
+            let body = serde_json::to_string(health).expect("couldn't serialize health report");
+            http::request(Method::POST, url, &[], Some(body)).await
+        }
+    };
+
+    result.map(drop)
+}
+
+/// Send an `observatory.heartbeat` gauge to Datadog.
+async fn send_datadog_metric(dd_api_key: &str) -> Result<(), Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let hostname = hostname::get().unwrap();
+
+    let body = json!({
+        "series": [{
+            "metric": DATADOG_METRIC,
+            "points": [[now, 1]],
+            "type": "gauge",
+            "host": hostname.to_string_lossy(),
+        }],
+    });
+
+    http::request(
+        Method::POST,
+        DATADOG_SERIES_URL,
+        &[("DD-API-KEY", dd_api_key)],
+        Some(body.to_string()),
+    )
+    .await
+    .map(drop)
+}
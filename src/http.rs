@@ -0,0 +1,88 @@
+//! Outbound HTTP requests, shared by notification sinks, heartbeats, cluster peers and the API
+//! client.
+
+use crate::error::{Error, ErrorKind};
+use hyper::{body, body::Bytes, Body, Client, Method, Request, StatusCode};
+use hyper_tls::HttpsConnector;
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Amount of time to wait for a response.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Make a request with the given headers and optional JSON body, returning the body of a
+/// successful response.
+///
+/// Unsuccessful responses are reported using the `error` field of their JSON body if they have
+/// one, and their status otherwise.
+pub async fn request(
+    method: Method,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<String>,
+) -> Result<Bytes, Error> {
+    let (status, bytes) = send(method, url, headers, body).await?;
+
+    if !status.is_success() {
+        let message = serde_json::from_slice::<serde_json::Value>(&bytes)
+            .ok()
+            .and_then(|json| json["error"].as_str().map(ToOwned::to_owned))
+            .unwrap_or_else(|| format!("responded with status {status}"));
+
+        return Err(http_error(message));
+    }
+
+    Ok(bytes)
+}
+
+/// Make a request with the given headers and optional JSON body, returning the status and body
+/// of the response, whether or not it was successful.
+pub async fn send(
+    method: Method,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<String>,
+) -> Result<(StatusCode, Bytes), Error> {
+    let mut builder = Request::builder().method(method).uri(url);
+
+    if body.is_some() {
+        builder = builder.header("Content-Type", "application/json");
+    }
+
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+
+    let request = builder
+        .body(body.map(Body::from).unwrap_or_else(Body::empty))
+        .map_err(|err| http_error(format!("invalid request: {err}")))?;
+
+    let client = Client::builder().build::<_, Body>(HttpsConnector::new());
+
+    let response = timeout(TIMEOUT, client.request(request))
+        .await
+        .map_err(|_| http_error("request timed out".to_owned()))?
+        .map_err(|err| http_error(format!("request failed: {err}")))?;
+
+    let status = response.status();
+    let bytes = body::to_bytes(response.into_body())
+        .await
+        .map_err(|err| http_error(format!("couldn't read response: {err}")))?;
+
+    Ok((status, bytes))
+}
+
+/// Make a request, deserializing a successful JSON response.
+pub async fn request_json<T: serde::de::DeserializeOwned>(
+    method: Method,
+    url: &str,
+    body: Option<String>,
+) -> Result<T, Error> {
+    let bytes = request(method, url, &[], body).await?;
+
+    serde_json::from_slice(&bytes).map_err(|err| http_error(format!("invalid response: {err}")))
+}
+
+fn http_error(message: String) -> Error {
+    ErrorKind::Http.context(message).into()
+}
@@ -15,16 +15,35 @@
     unused_qualifications
 )]
 
+mod ack;
+mod api;
 pub mod application;
 mod chain_monitor;
 mod chain_state;
 mod client_manager;
+mod cluster;
 pub mod commands;
 pub mod config;
+mod consensus_key;
+mod consensus_monitor;
+mod dashboard;
 pub mod datadog;
+mod dispatch;
 pub mod error;
+mod health;
+mod heartbeat;
+mod http;
+mod node_monitor;
 mod pager;
 pub mod prelude;
+mod proposer;
+mod routing;
+mod signing_report;
+mod silence;
+mod sink;
+mod status;
+mod template;
+mod upgrade;
 
 /// URL type.
 // TODO(tarcieri): use `url` crate?
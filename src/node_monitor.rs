@@ -0,0 +1,126 @@
+//! Node monitor: polls `/status` and `/net_info` on one of our own nodes
+//! (e.g. a sentry or validator) to check it's in sync and well connected.
+
+use crate::{client_manager::rpc_error_message, config::NodeConfig};
+use serde::Serialize;
+use std::time::Duration;
+use tendermint::{block, chain, Time};
+use tendermint_rpc::{Client, HttpClient};
+use tokio::time::timeout;
+
+/// How often each node is polled.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Amount of time to wait for each RPC response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Node monitor
+#[derive(Debug)]
+pub struct NodeMonitor {
+    /// Node configuration.
+    config: NodeConfig,
+
+    /// RPC client for the node.
+    client: HttpClient,
+}
+
+impl NodeMonitor {
+    /// Create a new node monitor.
+    pub fn new(config: NodeConfig) -> Result<Self, tendermint_rpc::Error> {
+        let client = HttpClient::new(config.rpc_url.as_str())?;
+        Ok(Self { config, client })
+    }
+
+    /// Get the node's configuration.
+    pub fn config(&self) -> &NodeConfig {
+        &self.config
+    }
+
+    /// Poll the node, comparing its height against the network's best known height.
+    pub async fn poll(&self, best_height: Option<block::Height>) -> NodeStatus {
+        let mut status = NodeStatus {
+            name: self.config.name.clone(),
+            chain_id: self.config.chain_id.clone(),
+            url: self.config.rpc_url.clone(),
+            height: None,
+            lag: None,
+            catching_up: false,
+            version: None,
+            peers: None,
+            last_error: None,
+            updated_at: Time::now(),
+        };
+
+        let response = match timeout(REQUEST_TIMEOUT, self.client.status()).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(err)) => {
+                status.last_error = Some(rpc_error_message(&err));
+                return status;
+            }
+            Err(_) => {
+                status.last_error = Some("/status request timed out".to_owned());
+                return status;
+            }
+        };
+
+        if response.node_info.network != self.config.chain_id {
+            status.last_error = Some(format!(
+                "node is on chain '{}' (expecting {})",
+                response.node_info.network, self.config.chain_id
+            ));
+            return status;
+        }
+
+        let height = response.sync_info.latest_block_height;
+        status.height = Some(height);
+        status.lag = Some(
+            best_height
+                .map(|best| best.value().saturating_sub(height.value()))
+                .unwrap_or_default(),
+        );
+        status.catching_up = response.sync_info.catching_up;
+        status.version = Some(response.node_info.version.to_string());
+
+        match timeout(REQUEST_TIMEOUT, self.client.net_info()).await {
+            Ok(Ok(net_info)) => status.peers = Some(net_info.n_peers),
+            Ok(Err(err)) => status.last_error = Some(rpc_error_message(&err)),
+            Err(_) => status.last_error = Some("/net_info request timed out".to_owned()),
+        }
+
+        status
+    }
+}
+
+/// Snapshot of one of our nodes' state.
+#[derive(Clone, Debug, Serialize)]
+pub struct NodeStatus {
+    /// Name of the node.
+    pub name: String,
+
+    /// Chain ID the node is expected to serve.
+    pub chain_id: chain::Id,
+
+    /// RPC URL of the node.
+    pub url: String,
+
+    /// Latest block height reported by the node.
+    pub height: Option<block::Height>,
+
+    /// Number of blocks the node is behind the network's best known height.
+    pub lag: Option<u64>,
+
+    /// Is the node catching up (block syncing)?
+    pub catching_up: bool,
+
+    /// CometBFT version the node is running.
+    pub version: Option<String>,
+
+    /// Number of connected peers.
+    pub peers: Option<u64>,
+
+    /// Error returned by the most recent failed request.
+    pub last_error: Option<String>,
+
+    /// Time this snapshot was taken.
+    pub updated_at: Time,
+}
@@ -1,22 +1,49 @@
 use crate::{
-    datadog::{send_stream_event, StreamEvent},
+    ack::{Ack, AckRequest},
+    chain_state::Misbehavior,
+    cluster::{Cluster, SharedState},
+    config::{AlarmsConfig, SilenceConfig},
+    consensus_monitor::{ConsensusStatus, RoundVoteStatus},
+    dispatch::Dispatcher,
+    error::{Error, ErrorKind},
+    node_monitor::NodeStatus,
     prelude::*,
+    silence::{Silence, Silences},
+    upgrade::UpgradePlan,
 };
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeMap as Map,
+    collections::{BTreeMap as Map, BTreeSet as Set},
     fmt::{self, Debug},
     future::Future,
+    mem,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
-    time::Duration,
-    time::SystemTime,
+    time::{Duration, Instant},
 };
-use tendermint::chain;
+use tendermint::{block, chain, Time};
+use tokio::sync::Notify;
 use tower::{Service, ServiceExt};
-use tracing::warn;
 
-/// Monitor the pager service for alarms, reporting them to the configured alerting service.
-pub async fn monitor_pager_service(alerting_interval: Duration, mut service: PagerBuffer) {
+/// Subject of alarms about a chain as a whole rather than a particular validator or node.
+pub const CHAIN_SUBJECT: &str = "chain";
+
+/// Monitor the pager service for alarms, reporting them (and their resolution) to the sinks
+/// they're routed to.
+///
+/// Alarms are collected every `alerting_interval`, or as soon as `notify` signals that a new
+/// critical alarm was raised. Collection also happens early when a firing alarm is due to be
+/// escalated, or when the `dispatcher` has grouped alarms due to be sent.
+///
+/// Only the leader of the `cluster` sends alarms; other instances just collect them.
+pub async fn monitor_pager_service(
+    alerting_interval: Duration,
+    mut service: PagerBuffer,
+    notify: Arc<Notify>,
+    mut dispatcher: Dispatcher,
+    cluster: Cluster,
+) {
     loop {
         let response = service
             .ready()
@@ -26,70 +53,124 @@ pub async fn monitor_pager_service(alerting_interval: Duration, mut service: Pag
             .await
             .expect("PagerService error");
 
-        let alarms = match response {
-            PagerResponse::GetAlarms(alarms) => alarms,
+        let notifications = match response {
+            PagerResponse::GetAlarms(notifications) => notifications,
             other => panic!("unexpected PagerService response: {:?}", other),
         };
 
-        for alarm in alarms {
-            report_alarm(alarm).await;
-        }
-
-        tokio::time::sleep(alerting_interval).await;
-    }
-}
-
-/// Report a triggered alarm to the pager service.
-async fn report_alarm(alarm: PagerAlarm) {
-    warn!(
-        "[{}] missed {} blocks!",
-        alarm.chain_id, alarm.missed_blocks
-    );
-
-    dbg!(&alarm);
-    let config = APP.config();
-    let dd_config = config.datadog.as_ref().expect("no datadog config");
-    let dd_api_key = dd_config.dd_api_key.clone().expect("no datadog API key");
-    let hostname = hostname::get().unwrap();
-    let mut ddtags = Map::new();
-    ddtags.insert("env".to_owned(), "staging".to_owned());
-    let stream_event = StreamEvent {
-        aggregation_key: None,
-        alert_type: Some(crate::datadog::AlertType::Error),
-        date_happened: Some(SystemTime::now()),
-        device_name: None,
-        hostname: Some(hostname.to_string_lossy().to_string()),
-        priority: Some(crate::datadog::Priority::Normal),
-        related_event_id: None,
-        tags: Some(ddtags),
-        // Text field must contain @pagerduty to trigger alert
-        text: format!("@pagerduty event: {:?}", &alarm),
-        title: alarm.to_string(),
-    };
+        let response = service
+            .ready()
+            .await
+            .expect("PagerService not ready")
+            .call(PagerRequest::GetResolved)
+            .await
+            .expect("PagerService error");
+
+        let resolved = match response {
+            PagerResponse::Resolved(resolved) => resolved,
+            other => panic!("unexpected PagerService response: {:?}", other),
+        };
 
-    // send stream event to datadog which forwards to pagerduty
-    let stream_event = send_stream_event(&stream_event, dd_api_key).await;
-    match stream_event {
-        Ok(()) => {
-            dbg!("event sent to datadog");
+        let mut next_collection = alerting_interval;
+        let now = Instant::now();
+
+        if cluster.is_leader() {
+            for notification in notifications {
+                if let Some(escalation) = dispatcher
+                    .router()
+                    .next_escalation(&notification.alarm, notification.firing_for)
+                {
+                    next_collection = next_collection.min(escalation);
+                }
+
+                dispatcher.enqueue(notification, now);
+            }
+
+            for alarm in resolved {
+                dispatcher.resolve(alarm);
+            }
+        } else if !notifications.is_empty() {
+            debug!(
+                "not the leader; leaving {} alarm(s) to {}",
+                notifications.len(),
+                cluster.status().leader
+            );
+        }
+
+        dispatcher.flush(&cluster.instance_id()).await;
+
+        if let Some(flush) = dispatcher.next_flush(Instant::now()) {
+            next_collection = next_collection.min(flush);
         }
-        Err(_err) => {
-            warn!("unable to sent event to datadog");
+
+        tokio::select! {
+            _ = tokio::time::sleep(next_collection) => (),
+            _ = notify.notified() => (),
         }
     }
 }
 
 /// Pager service.
 pub struct PagerService {
-    /// Chain registry.
-    chains: Map<chain::Id, usize>,
+    /// Alarms which have fired since the last time alarms were collected.
+    pending: Map<AlarmKey, PagerAlarm>,
+
+    /// Alarms which are currently firing, retained until they're resolved.
+    firing: Map<AlarmKey, PagerAlarm>,
+
+    /// Time each firing alarm started firing.
+    firing_since: Map<AlarmKey, Instant>,
+
+    /// Firing alarms which have been collected to be reported.
+    reported: Set<AlarmKey>,
+
+    /// Reported alarms which have resolved since resolutions were last collected.
+    resolved: Map<AlarmKey, PagerAlarm>,
 
     /// Number of missing blocks after which an alert is created.
     missed_blocks_threshold: usize,
 
     /// Number of blocks after which we consider signing to be recovered.
     recovered_after_threshold: usize,
+
+    /// Ratio of nil votes to blocks in the history window after which an alert is created.
+    nil_votes_threshold: f64,
+
+    /// Relative change in voting power after which an alert is created.
+    voting_power_change_threshold: f64,
+
+    /// Voting power each validator's changes are measured against in place of the chain
+    /// monitor's rolling reference, from when a change is detected until it's resolved or
+    /// acknowledged and the rolling reference has caught up.
+    power_references: Map<AlarmKey, PowerReference>,
+
+    /// Notified when a new critical alarm is raised so it can be reported immediately.
+    notify: Arc<Notify>,
+
+    /// Chains undergoing a planned upgrade, for which alarms which are expected during the
+    /// upgrade aren't raised.
+    upgrading: Set<chain::Id>,
+
+    /// Silences during which alarms are tracked but not reported.
+    silences: Silences,
+
+    /// Acknowledgements of firing alarms, which aren't reported again until they expire.
+    acks: Map<AlarmKey, Ack>,
+
+    /// Amount of time after which acknowledgements expire unless they set their own TTL.
+    ack_ttl: Duration,
+}
+
+/// Alarms are keyed by chain ID, kind and subject (e.g. validator name).
+pub type AlarmKey = (chain::Id, AlarmKind, String);
+
+/// Voting power a validator's changes are measured against, along with its latest voting power.
+#[derive(Copy, Clone, Debug)]
+struct PowerReference {
+    reference: u64,
+    power: u64,
 }
+
 /// PagerFuture future returned from the service
 pub type PagerFuture =
     Pin<Box<dyn Future<Output = Result<PagerResponse, PagerError>> + Send + 'static>>;
@@ -98,35 +179,599 @@ pub type PagerFuture =
 pub type PagerBuffer = tower::buffer::Buffer<PagerRequest, PagerFuture>;
 
 impl PagerService {
-    pub fn new(missed_blocks_threshold: usize, recovered_after_threshold: usize) -> Self {
+    pub fn new(
+        missed_blocks_threshold: usize,
+        recovered_after_threshold: usize,
+        nil_votes_threshold: f64,
+        voting_power_change_threshold: f64,
+    ) -> Self {
         Self {
-            chains: Map::default(),
+            pending: Map::default(),
+            firing: Map::default(),
+            firing_since: Map::default(),
+            reported: Set::default(),
+            resolved: Map::default(),
             missed_blocks_threshold,
             recovered_after_threshold,
+            nil_votes_threshold,
+            voting_power_change_threshold,
+            power_references: Map::default(),
+            notify: Arc::new(Notify::new()),
+            upgrading: Set::default(),
+            silences: Silences::default(),
+            acks: Map::default(),
+            ack_ttl: AlarmsConfig::default().ack_ttl(),
+        }
+    }
+
+    /// Use the given silences.
+    pub fn with_silences(mut self, silences: Silences) -> Self {
+        self.silences = silences;
+        self
+    }
+
+    /// Expire acknowledgements after the given amount of time unless they set their own TTL.
+    pub fn with_ack_ttl(mut self, ack_ttl: Duration) -> Self {
+        self.ack_ttl = ack_ttl;
+        self
+    }
+
+    /// Get a handle which is notified whenever a new critical alarm is raised.
+    pub fn notifier(&self) -> Arc<Notify> {
+        self.notify.clone()
+    }
+
+    fn handle_event(&mut self, event: SigningEvent) {
+        match event.voting_power {
+            // Validators outside of the active set can't sign, so report that instead of misses
+            Some(0) => {
+                self.resolve(
+                    event.chain_id.clone(),
+                    AlarmKind::MissedBlocks,
+                    &event.validator,
+                );
+                self.resolve(
+                    event.chain_id.clone(),
+                    AlarmKind::VotingPowerChange,
+                    &event.validator,
+                );
+                self.raise(PagerAlarm {
+                    chain_id: event.chain_id.clone(),
+                    kind: AlarmKind::NotInValidatorSet,
+                    severity: Severity::Critical,
+                    subject: event.validator.clone(),
+                    message: "is not in the active validator set (jailed or unbonded?)".to_owned(),
+                    details: event.details(),
+                });
+            }
+            Some(_) => {
+                self.resolve(
+                    event.chain_id.clone(),
+                    AlarmKind::NotInValidatorSet,
+                    &event.validator,
+                );
+                self.handle_voting_power(&event);
+                self.handle_missed_blocks(&event);
+            }
+            None => self.handle_missed_blocks(&event),
+        }
+
+        match &event.misbehavior {
+            Some(misbehavior) => self.raise(PagerAlarm {
+                chain_id: event.chain_id.clone(),
+                kind: AlarmKind::Evidence,
+                severity: Severity::Critical,
+                subject: event.validator.clone(),
+                message: format!("committed misbehavior: {misbehavior}"),
+                details: event.details(),
+            }),
+            None => self.resolve(
+                event.chain_id.clone(),
+                AlarmKind::Evidence,
+                &event.validator,
+            ),
+        }
+
+        match event.missed_proposal {
+            Some(height) => self.raise(PagerAlarm {
+                chain_id: event.chain_id.clone(),
+                kind: AlarmKind::MissedProposal,
+                severity: Severity::Warning,
+                subject: event.validator.clone(),
+                message: format!("missed its proposal at height {height}"),
+                details: AlarmDetails {
+                    height: Some(height),
+                    ..event.details()
+                },
+            }),
+            None => self.resolve(
+                event.chain_id.clone(),
+                AlarmKind::MissedProposal,
+                &event.validator,
+            ),
+        }
+
+        if event.blocks > 0
+            && event.nil_votes as f64 / event.blocks as f64 >= self.nil_votes_threshold
+        {
+            self.raise(PagerAlarm {
+                chain_id: event.chain_id.clone(),
+                kind: AlarmKind::NilVotes,
+                severity: Severity::Warning,
+                subject: event.validator.clone(),
+                message: format!(
+                    "precommitted nil in {} of {} blocks",
+                    event.nil_votes, event.blocks
+                ),
+                details: event.details(),
+            });
+        } else {
+            self.resolve(event.chain_id, AlarmKind::NilVotes, &event.validator);
         }
     }
 
-    fn handle_event(&mut self, chain_id: chain::Id, missed_blocks: usize, recent_blocks: usize) {
-        if recent_blocks >= self.recovered_after_threshold {
-            self.chains.remove(&chain_id);
-        } else if missed_blocks >= self.missed_blocks_threshold {
-            self.chains.insert(chain_id, missed_blocks);
+    fn handle_missed_blocks(&mut self, event: &SigningEvent) {
+        if event.recent_blocks >= self.recovered_after_threshold {
+            self.resolve(
+                event.chain_id.clone(),
+                AlarmKind::MissedBlocks,
+                &event.validator,
+            );
+        } else if event.missed_blocks >= self.missed_blocks_threshold {
+            self.raise(PagerAlarm {
+                chain_id: event.chain_id.clone(),
+                kind: AlarmKind::MissedBlocks,
+                severity: Severity::Critical,
+                subject: event.validator.clone(),
+                message: format!("missed {} blocks!", event.missed_blocks),
+                details: event.details(),
+            });
         }
     }
 
-    fn get_alarms(&mut self) -> Vec<PagerAlarm> {
-        let result = self
-            .chains
-            .iter()
-            .map(|(chain_id, missed_blocks)| PagerAlarm {
+    fn handle_voting_power(&mut self, event: &SigningEvent) {
+        let (Some(power), Some(rolling_reference)) =
+            (event.voting_power, event.reference_voting_power)
+        else {
+            return;
+        };
+
+        let key = (
+            event.chain_id.clone(),
+            AlarmKind::VotingPowerChange,
+            event.validator.clone(),
+        );
+        let reference = self
+            .power_references
+            .get(&key)
+            .map_or(rolling_reference, |pinned| pinned.reference);
+
+        if self.power_changed(reference, power) {
+            self.power_references
+                .insert(key, PowerReference { reference, power });
+
+            let change = (power as f64 - reference as f64) / reference as f64;
+            self.raise(PagerAlarm {
+                chain_id: event.chain_id.clone(),
+                kind: AlarmKind::VotingPowerChange,
+                severity: Severity::Warning,
+                subject: event.validator.clone(),
+                message: format!(
+                    "voting power changed from {reference} to {power} ({:+.1}%)",
+                    change * 100.0
+                ),
+                details: event.details(),
+            });
+        } else {
+            // Measure changes against the rolling reference again once it has caught up
+            if !self.power_changed(rolling_reference, reference) {
+                self.power_references.remove(&key);
+            } else if let Some(pinned) = self.power_references.get_mut(&key) {
+                pinned.power = power;
+            }
+
+            self.resolve(
+                event.chain_id.clone(),
+                AlarmKind::VotingPowerChange,
+                &event.validator,
+            );
+        }
+    }
+
+    /// Has voting power changed enough relative to the reference to raise an alarm?
+    fn power_changed(&self, reference: u64, power: u64) -> bool {
+        let change = (power as f64 - reference as f64) / reference as f64;
+        reference > 0 && change.abs() >= self.voting_power_change_threshold
+    }
+
+    fn handle_chain_event(&mut self, event: ChainEvent) {
+        let ChainEvent {
+            chain_id,
+            height,
+            halted_for,
+            halt_after,
+            upgrade,
+            upgrade_resume_deadline,
+        } = event;
+
+        let Some(plan) = upgrade else {
+            if self.upgrading.remove(&chain_id) {
+                info!("[{chain_id}] upgrade window passed; resuming alarms");
+            }
+
+            self.resolve(chain_id.clone(), AlarmKind::UpgradeStalled, CHAIN_SUBJECT);
+
+            if halted_for >= halt_after {
+                let message = match height {
+                    Some(height) => format!(
+                        "halted: no new block after height {height} for {}s",
+                        halted_for.as_secs()
+                    ),
+                    None => format!(
+                        "halted: no block received in {}s since startup",
+                        halted_for.as_secs()
+                    ),
+                };
+
+                self.raise(PagerAlarm {
+                    chain_id,
+                    kind: AlarmKind::ChainHalt,
+                    severity: Severity::Critical,
+                    subject: CHAIN_SUBJECT.to_owned(),
+                    message,
+                    details: AlarmDetails {
+                        height,
+                        ..AlarmDetails::default()
+                    },
+                });
+            } else {
+                self.resolve(chain_id, AlarmKind::ChainHalt, CHAIN_SUBJECT);
+            }
+
+            return;
+        };
+
+        if self.upgrading.insert(chain_id.clone()) {
+            info!("[{chain_id}] approaching {plan}; suppressing halt and missed block alarms");
+        }
+
+        self.resolve(chain_id.clone(), AlarmKind::ChainHalt, CHAIN_SUBJECT);
+
+        if halted_for >= upgrade_resume_deadline {
+            self.raise(PagerAlarm {
+                chain_id,
+                kind: AlarmKind::UpgradeStalled,
+                severity: Severity::Critical,
+                subject: CHAIN_SUBJECT.to_owned(),
+                message: format!(
+                    "hasn't resumed after {plan}: no new block after height {} for {}s",
+                    height.unwrap_or(plan.height),
+                    halted_for.as_secs()
+                ),
+                details: AlarmDetails {
+                    height,
+                    ..AlarmDetails::default()
+                },
+            });
+        } else {
+            self.resolve(chain_id, AlarmKind::UpgradeStalled, CHAIN_SUBJECT);
+        }
+    }
+
+    fn handle_node_event(&mut self, event: NodeEvent) {
+        let NodeEvent {
+            status,
+            max_lag,
+            min_peers,
+        } = event;
+        let chain_id = status.chain_id;
+        let node = status.name;
+
+        let Some(height) = status.height else {
+            self.raise(PagerAlarm {
+                chain_id,
+                kind: AlarmKind::NodeUnreachable,
+                severity: Severity::Critical,
+                subject: node,
+                message: format!(
+                    "is unreachable: {}",
+                    status.last_error.as_deref().unwrap_or("unknown error")
+                ),
+                details: AlarmDetails::default(),
+            });
+            return;
+        };
+
+        self.resolve(chain_id.clone(), AlarmKind::NodeUnreachable, &node);
+
+        let lag = status.lag.unwrap_or_default();
+
+        if status.catching_up || lag > max_lag {
+            let state = if status.catching_up {
+                "is catching up"
+            } else {
+                "is behind"
+            };
+
+            self.raise(PagerAlarm {
                 chain_id: chain_id.clone(),
-                missed_blocks: *missed_blocks,
+                kind: AlarmKind::NodeBehind,
+                severity: Severity::Critical,
+                subject: node.clone(),
+                message: format!("{state} at height {height} ({lag} blocks behind the network)"),
+                details: AlarmDetails {
+                    height: Some(height),
+                    ..AlarmDetails::default()
+                },
+            });
+        } else {
+            self.resolve(chain_id.clone(), AlarmKind::NodeBehind, &node);
+        }
+
+        match status.peers {
+            Some(peers) if peers < min_peers => self.raise(PagerAlarm {
+                chain_id,
+                kind: AlarmKind::NodePeers,
+                severity: Severity::Warning,
+                subject: node,
+                message: format!("has {peers} peers (minimum {min_peers})"),
+                details: AlarmDetails {
+                    height: Some(height),
+                    ..AlarmDetails::default()
+                },
+            }),
+            Some(_) => self.resolve(chain_id, AlarmKind::NodePeers, &node),
+            None => (),
+        }
+    }
+
+    fn handle_consensus_event(&mut self, event: ConsensusEvent) {
+        let ConsensusEvent { status, max_round } = event;
+
+        if status.round < max_round {
+            self.resolve(status.chain_id, AlarmKind::RoundEscalation, CHAIN_SUBJECT);
+            return;
+        }
+
+        let mut message = format!(
+            "height {} reached round {} ({} step",
+            status.height, status.round, status.step
+        );
+
+        if let Some(prevotes) = status.prevotes {
+            message.push_str(&format!(", {:.0}% prevoted", prevotes * 100.0));
+        }
+
+        message.push(')');
+
+        let missing = status
+            .validators
+            .iter()
+            .filter(|validator| validator.prevote == RoundVoteStatus::Missing)
+            .map(|validator| validator.name.as_str())
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            message.push_str("; our validators prevoted");
+        } else {
+            message.push_str(&format!("; no prevote from {}", missing.join(", ")));
+        }
+
+        self.raise(PagerAlarm {
+            chain_id: status.chain_id,
+            kind: AlarmKind::RoundEscalation,
+            severity: Severity::Warning,
+            subject: CHAIN_SUBJECT.to_owned(),
+            message,
+            details: AlarmDetails {
+                height: Some(status.height),
+                ..AlarmDetails::default()
+            },
+        });
+    }
+
+    /// Replace all alarms of the given kind on the given chain.
+    fn sync(&mut self, chain_id: chain::Id, kind: AlarmKind, alarms: Vec<PagerAlarm>) {
+        let stale = self
+            .firing
+            .keys()
+            .filter(|(id, k, subject)| {
+                id == &chain_id
+                    && *k == kind
+                    && !alarms.iter().any(|alarm| &alarm.subject == subject)
             })
-            .collect();
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for (chain_id, kind, subject) in stale {
+            self.resolve(chain_id, kind, &subject);
+        }
+
+        for alarm in alarms {
+            self.raise(alarm);
+        }
+    }
+
+    fn raise(&mut self, alarm: PagerAlarm) {
+        if alarm.kind.expected_during_upgrades()
+            && self.upgrading.contains(&alarm.chain_id)
+            && !self.firing.contains_key(&alarm.key())
+        {
+            debug!("suppressed alarm during upgrade: {alarm}");
+            return;
+        }
+
+        if alarm.severity == Severity::Critical && !self.firing.contains_key(&alarm.key()) {
+            self.notify.notify_one();
+        }
+
+        self.firing_since
+            .entry(alarm.key())
+            .or_insert_with(Instant::now);
+        self.resolved.remove(&alarm.key());
+        self.pending.insert(alarm.key(), alarm.clone());
+        self.firing.insert(alarm.key(), alarm);
+    }
+
+    fn resolve(&mut self, chain_id: chain::Id, kind: AlarmKind, subject: &str) {
+        let key = (chain_id, kind, subject.to_owned());
+        self.pending.remove(&key);
+        self.firing_since.remove(&key);
+        self.acks.remove(&key);
+
+        if let Some(alarm) = self.firing.remove(&key)
+            && self.reported.remove(&key)
+        {
+            self.resolved.insert(key, alarm);
+        }
+    }
+
+    /// Acknowledge the firing alarms matching the given request.
+    fn ack(&mut self, request: AckRequest) -> Result<Vec<Ack>, Error> {
+        let at = Time::now();
+        let ttl = request
+            .ttl_secs
+            .map(Duration::from_secs)
+            .unwrap_or(self.ack_ttl);
+        let expires = (at + ttl)
+            .map_err(|err| ErrorKind::Config.context(format!("invalid ack TTL: {err}")))?;
+
+        let mut acks = vec![];
+
+        for (key, alarm) in &self.firing {
+            if !request.matches(alarm) {
+                continue;
+            }
+
+            let ack = Ack {
+                chain_id: alarm.chain_id.clone(),
+                kind: alarm.kind,
+                subject: alarm.subject.clone(),
+                by: request.by.clone(),
+                note: request.note.clone(),
+                at,
+                expires,
+            };
+
+            info!("alarm acknowledged by {}: {alarm}", ack.by);
+            self.acks.insert(key.clone(), ack.clone());
+            acks.push(ack);
+
+            // Escalation starts over from the acknowledgement
+            self.firing_since.insert(key.clone(), Instant::now());
+
+            // Acknowledging a voting power change accepts the new voting power
+            if let Some(pinned) = self.power_references.get_mut(key) {
+                pinned.reference = pinned.power;
+            }
+        }
+
+        Ok(acks)
+    }
+
+    /// Get acknowledgements which haven't expired.
+    fn acks(&mut self) -> Vec<Ack> {
+        self.expire_acks(Time::now());
+        self.acks.values().cloned().collect()
+    }
+
+    /// Remove expired acknowledgements. Their alarms' escalation starts over from the first step.
+    fn expire_acks(&mut self, now: Time) {
+        let firing_since = &mut self.firing_since;
+
+        self.acks.retain(|key, ack| {
+            let active = ack.is_active(now);
+
+            if !active && let Some(since) = firing_since.get_mut(key) {
+                *since = Instant::now();
+            }
+
+            active
+        });
+    }
+
+    /// Merge a peer's runtime silences, and its acknowledgements of alarms firing here.
+    fn merge_shared_state(&mut self, shared: SharedState) {
+        let now = Time::now();
+
+        for ack in shared.acks {
+            let key = (ack.chain_id.clone(), ack.kind, ack.subject.clone());
+
+            if !ack.is_active(now)
+                || !self.firing.contains_key(&key)
+                || self
+                    .acks
+                    .get(&key)
+                    .is_some_and(|existing| existing.at >= ack.at)
+            {
+                continue;
+            }
+
+            info!(
+                "alarm acknowledged by {} on a peer: {}",
+                ack.by, self.firing[&key]
+            );
+
+            if let Some(pinned) = self.power_references.get_mut(&key) {
+                pinned.reference = pinned.power;
+            }
+
+            self.firing_since.insert(key.clone(), Instant::now());
+            self.acks.insert(key, ack);
+        }
+
+        if let Err(err) = self.silences.merge(shared.silences) {
+            warn!("couldn't persist a peer's silences: {err}");
+        }
+    }
+
+    /// Collect alarms to be reported. Silenced alarms are left pending until their silence ends
+    /// (or they're resolved), while acknowledged alarms aren't reported until their
+    /// acknowledgement expires.
+    fn get_alarms(&mut self) -> Vec<Notification> {
+        let now = Time::now();
+        self.expire_acks(now);
+
+        let silences = &self.silences;
+        let acks = &self.acks;
+        let firing_since = &self.firing_since;
+        let reported = &mut self.reported;
+        let mut result = vec![];
+
+        self.pending.retain(|key, alarm| {
+            if let Some(ack) = acks.get(key) {
+                debug!("alarm acknowledged by {}: {alarm}", ack.by);
+                false
+            } else if let Some(silence) = silences.silencing(alarm, now) {
+                debug!(
+                    "alarm silenced by {} ({}): {alarm}",
+                    silence.id, silence.reason
+                );
+                true
+            } else {
+                reported.insert(key.clone());
+                result.push(Notification {
+                    alarm: alarm.clone(),
+                    firing_for: firing_since
+                        .get(key)
+                        .map(Instant::elapsed)
+                        .unwrap_or_default(),
+                });
+                false
+            }
+        });
 
-        self.chains.clear();
         result
     }
+
+    /// Collect reported alarms which have resolved since this was last called.
+    fn take_resolved(&mut self) -> Vec<PagerAlarm> {
+        mem::take(&mut self.resolved).into_values().collect()
+    }
+
+    fn active_alarms(&self) -> Vec<PagerAlarm> {
+        self.firing.values().cloned().collect()
+    }
 }
 
 impl Service<PagerRequest> for PagerService {
@@ -140,54 +785,385 @@ impl Service<PagerRequest> for PagerService {
 
     fn call(&mut self, request: PagerRequest) -> Self::Future {
         let response = match request {
-            PagerRequest::Event {
+            PagerRequest::Event(event) => {
+                self.handle_event(event);
+                Ok(PagerResponse::Event)
+            }
+            PagerRequest::Chain(event) => {
+                self.handle_chain_event(event);
+                Ok(PagerResponse::Event)
+            }
+            PagerRequest::Consensus(event) => {
+                self.handle_consensus_event(event);
+                Ok(PagerResponse::Event)
+            }
+            PagerRequest::Node(event) => {
+                self.handle_node_event(event);
+                Ok(PagerResponse::Event)
+            }
+            PagerRequest::Sync {
                 chain_id,
-                missed_blocks,
-                recent_blocks,
+                kind,
+                alarms,
             } => {
-                self.handle_event(chain_id, missed_blocks, recent_blocks);
+                self.sync(chain_id, kind, alarms);
+                Ok(PagerResponse::Event)
+            }
+            PagerRequest::AddSilence(config) => {
+                Ok(PagerResponse::AddedSilence(self.silences.add(&config)))
+            }
+            PagerRequest::RemoveSilence(id) => {
+                Ok(PagerResponse::RemovedSilence(self.silences.remove(&id)))
+            }
+            PagerRequest::GetSilences => Ok(PagerResponse::Silences(self.silences.list())),
+            PagerRequest::Ack(request) => Ok(PagerResponse::Acked(self.ack(request))),
+            PagerRequest::GetAcks => Ok(PagerResponse::Acks(self.acks())),
+            PagerRequest::GetSharedState => Ok(PagerResponse::SharedState(SharedState {
+                acks: self.acks(),
+                silences: self.silences.runtime().to_vec(),
+            })),
+            PagerRequest::MergeSharedState(shared) => {
+                self.merge_shared_state(shared);
                 Ok(PagerResponse::Event)
             }
             PagerRequest::GetAlarms => Ok(PagerResponse::GetAlarms(self.get_alarms())),
+            PagerRequest::GetResolved => Ok(PagerResponse::Resolved(self.take_resolved())),
+            PagerRequest::ActiveAlarms => Ok(PagerResponse::ActiveAlarms(self.active_alarms())),
         };
         Box::pin(async { response })
     }
 }
 
 /// Pager alarms which indicate something is wrong and a page should be sent.
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct PagerAlarm {
     /// Chain ID the alarm is for.
     pub chain_id: chain::Id,
 
-    /// Number of missed blocks.
-    // TODO(tarcieri): other types of alarms?
-    pub missed_blocks: usize,
+    /// Kind of alarm.
+    pub kind: AlarmKind,
+
+    /// Severity of the alarm.
+    pub severity: Severity,
+
+    /// What the alarm is about within the chain, e.g. the validator name.
+    pub subject: String,
+
+    /// Human-readable description of the problem.
+    pub message: String,
+
+    /// Context about the alarm, available to sink templates.
+    pub details: AlarmDetails,
+}
+
+impl PagerAlarm {
+    /// Get the key identifying the alarm while it fires.
+    pub fn key(&self) -> AlarmKey {
+        (self.chain_id.clone(), self.kind, self.subject.clone())
+    }
 }
 
 impl fmt::Display for PagerAlarm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} missed {} blocks!", self.chain_id, self.missed_blocks)
+        write!(f, "[{}] {}: {}", self.chain_id, self.subject, self.message)
     }
 }
 
+/// Context about an alarm, beyond its message.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct AlarmDetails {
+    /// Moniker of the validator the alarm is about, if the RPC endpoint reports one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moniker: Option<String>,
+
+    /// Block height the alarm relates to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<block::Height>,
+
+    /// Number of blocks the validator missed in the history window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missed_blocks: Option<usize>,
+
+    /// Number of blocks in the history window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_blocks: Option<usize>,
+}
+
+impl AlarmDetails {
+    /// Get the percentage of blocks in the history window the validator signed.
+    pub fn uptime(&self) -> Option<f64> {
+        let missed = self.missed_blocks? as f64;
+        let window = self.window_blocks? as f64;
+        (window > 0.0).then(|| (window - missed) * 100.0 / window)
+    }
+}
+
+/// Alarm collected to be reported, along with how long it has been firing.
+#[derive(Clone, Debug)]
+pub struct Notification {
+    /// Alarm to report.
+    pub alarm: PagerAlarm,
+
+    /// Amount of time the alarm has been firing for.
+    pub firing_for: Duration,
+}
+
+/// Kinds of alarms.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlarmKind {
+    /// Validator missed too many blocks.
+    MissedBlocks,
+
+    /// Validator precommitted nil in too many blocks.
+    NilVotes,
+
+    /// Validator was scheduled to propose but the round advanced without its block.
+    MissedProposal,
+
+    /// Validator isn't in the active validator set, e.g. because it was jailed.
+    NotInValidatorSet,
+
+    /// Validator's voting power changed significantly.
+    VotingPowerChange,
+
+    /// Evidence of misbehavior (e.g. double signing) by our validator was included in a block.
+    Evidence,
+
+    /// Evidence of misbehavior by another validator was included in a block.
+    NetworkEvidence,
+
+    /// Consensus rounds are escalating at the current height.
+    RoundEscalation,
+
+    /// No new block has been produced for a while.
+    ChainHalt,
+
+    /// Chain hasn't resumed after halting for a planned upgrade.
+    UpgradeStalled,
+
+    /// One of our nodes isn't responding to RPC requests.
+    NodeUnreachable,
+
+    /// One of our nodes is catching up or behind the network.
+    NodeBehind,
+
+    /// One of our nodes has too few peers.
+    NodePeers,
+}
+
+impl AlarmKind {
+    /// Get the name the kind is serialized as, e.g. `missed_blocks`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlarmKind::MissedBlocks => "missed_blocks",
+            AlarmKind::NilVotes => "nil_votes",
+            AlarmKind::MissedProposal => "missed_proposal",
+            AlarmKind::NotInValidatorSet => "not_in_validator_set",
+            AlarmKind::VotingPowerChange => "voting_power_change",
+            AlarmKind::Evidence => "evidence",
+            AlarmKind::NetworkEvidence => "network_evidence",
+            AlarmKind::RoundEscalation => "round_escalation",
+            AlarmKind::ChainHalt => "chain_halt",
+            AlarmKind::UpgradeStalled => "upgrade_stalled",
+            AlarmKind::NodeUnreachable => "node_unreachable",
+            AlarmKind::NodeBehind => "node_behind",
+            AlarmKind::NodePeers => "node_peers",
+        }
+    }
+
+    /// Is this kind of alarm expected while a chain halts and restarts for a planned upgrade?
+    pub fn expected_during_upgrades(self) -> bool {
+        matches!(
+            self,
+            AlarmKind::MissedBlocks
+                | AlarmKind::NilVotes
+                | AlarmKind::MissedProposal
+                | AlarmKind::RoundEscalation
+                | AlarmKind::ChainHalt
+                | AlarmKind::NodeUnreachable
+                | AlarmKind::NodeBehind
+                | AlarmKind::NodePeers
+        )
+    }
+}
+
+/// Severity of an alarm.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Needs attention, but doesn't warrant a page.
+    Warning,
+
+    /// Page someone.
+    Critical,
+}
+
+impl Severity {
+    /// Get the name the severity is serialized as, e.g. `critical`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// Signing statistics for a validator, reported after each block.
+#[derive(Debug)]
+pub struct SigningEvent {
+    /// Chain ID where event occurred.
+    pub chain_id: chain::Id,
+
+    /// Name of the validator the event is for.
+    pub validator: String,
+
+    /// Moniker of the validator, if the RPC endpoint reports one.
+    pub moniker: Option<String>,
+
+    /// Latest imported block height.
+    pub height: block::Height,
+
+    /// Number of blocks in the history window.
+    pub blocks: usize,
+
+    /// Number of blocks that have been missed in the history window.
+    pub missed_blocks: usize,
+
+    /// Number of blocks since the last miss which have been signed.
+    pub recent_blocks: usize,
+
+    /// Number of blocks in the history window where the validator precommitted nil.
+    pub nil_votes: usize,
+
+    /// Height of the validator's latest scheduled proposal if it was missed (and alarming on
+    /// missed proposals is enabled).
+    pub missed_proposal: Option<block::Height>,
+
+    /// Current voting power, `0` if the validator isn't in the active set, or `None` if the
+    /// validator set isn't known.
+    pub voting_power: Option<u64>,
+
+    /// Voting power as of roughly one history window ago.
+    pub reference_voting_power: Option<u64>,
+
+    /// Latest misbehavior by the validator proven by evidence in the history window.
+    pub misbehavior: Option<Misbehavior>,
+}
+
+impl SigningEvent {
+    /// Get the details included in alarms about the validator.
+    fn details(&self) -> AlarmDetails {
+        AlarmDetails {
+            moniker: self.moniker.clone(),
+            height: Some(self.height),
+            missed_blocks: Some(self.missed_blocks),
+            window_blocks: Some(self.blocks),
+        }
+    }
+}
+
+/// Liveness of a chain, reported periodically whether or not new blocks are produced.
+#[derive(Debug)]
+pub struct ChainEvent {
+    /// Chain ID the event is for.
+    pub chain_id: chain::Id,
+
+    /// Latest imported block height, or `None` if no block has been imported since startup.
+    pub height: Option<block::Height>,
+
+    /// Amount of time since the latest block was imported, or since startup if none has been.
+    pub halted_for: Duration,
+
+    /// Amount of time without a new block after which an alarm is raised.
+    pub halt_after: Duration,
+
+    /// Planned upgrade the chain is within the window of, if any.
+    pub upgrade: Option<UpgradePlan>,
+
+    /// Amount of time the chain may stay halted for an upgrade before an alarm is raised.
+    pub upgrade_resume_deadline: Duration,
+}
+
+/// Consensus state of a chain, reported after each poll.
+#[derive(Debug)]
+pub struct ConsensusEvent {
+    /// Latest consensus state.
+    pub status: ConsensusStatus,
+
+    /// Round at or past which an alarm is raised.
+    pub max_round: u32,
+}
+
+/// Status of one of our nodes, reported after each poll.
+#[derive(Debug)]
+pub struct NodeEvent {
+    /// Latest status of the node.
+    pub status: NodeStatus,
+
+    /// Number of blocks behind the network after which an alarm is raised.
+    pub max_lag: u64,
+
+    /// Number of peers below which an alarm is raised.
+    pub min_peers: u64,
+}
+
 /// Requests sent to the pager service.
 #[derive(Debug)]
 pub enum PagerRequest {
-    /// Report information to the pager.
-    Event {
-        /// Chain ID where event occurred.
+    /// Report signing statistics to the pager.
+    Event(SigningEvent),
+
+    /// Report the liveness of a chain to the pager.
+    Chain(ChainEvent),
+
+    /// Report the consensus state of a chain to the pager.
+    Consensus(ConsensusEvent),
+
+    /// Report the status of one of our nodes to the pager.
+    Node(NodeEvent),
+
+    /// Replace all alarms of the given kind on the given chain, resolving any not included.
+    Sync {
+        /// Chain ID the alarms are for.
         chain_id: chain::Id,
 
-        /// Number of blocks that have been missed in the past 100.
-        missed_blocks: usize,
+        /// Kind of alarms.
+        kind: AlarmKind,
 
-        /// Number of blocks since the last miss which have been signed.
-        recent_blocks: usize,
+        /// Alarms which should be firing.
+        alarms: Vec<PagerAlarm>,
     },
 
+    /// Add a silence.
+    AddSilence(SilenceConfig),
+
+    /// Remove a silence by ID.
+    RemoveSilence(String),
+
+    /// Get all silences.
+    GetSilences,
+
+    /// Acknowledge firing alarms.
+    Ack(AckRequest),
+
+    /// Get acknowledgements which haven't expired.
+    GetAcks,
+
+    /// Get the acknowledgements and runtime silences shared with the rest of the cluster.
+    GetSharedState,
+
+    /// Merge the acknowledgements and runtime silences of a peer.
+    MergeSharedState(SharedState),
+
     /// Get alarms for the pager.
     GetAlarms,
+
+    /// Get reported alarms which have resolved since they were last collected.
+    GetResolved,
+
+    /// Get all alarms which are currently firing, without clearing them.
+    ActiveAlarms,
 }
 
 /// Response sent from the pager service.
@@ -197,7 +1173,31 @@ pub enum PagerResponse {
     Event,
 
     /// Get alarams response with the alarms.
-    GetAlarms(Vec<PagerAlarm>),
+    GetAlarms(Vec<Notification>),
+
+    /// Alarms which are currently firing.
+    ActiveAlarms(Vec<PagerAlarm>),
+
+    /// Reported alarms which have resolved.
+    Resolved(Vec<PagerAlarm>),
+
+    /// Silence which was added.
+    AddedSilence(Result<Silence, Error>),
+
+    /// Silence which was removed, if it existed.
+    RemovedSilence(Result<Option<Silence>, Error>),
+
+    /// All silences.
+    Silences(Vec<Silence>),
+
+    /// Alarms which were acknowledged.
+    Acked(Result<Vec<Ack>, Error>),
+
+    /// Acknowledgements which haven't expired.
+    Acks(Vec<Ack>),
+
+    /// Acknowledgements and runtime silences shared with the rest of the cluster.
+    SharedState(SharedState),
 }
 
 /// Error type.
@@ -211,3 +1211,489 @@ impl fmt::Display for PagerError {
 }
 
 impl std::error::Error for PagerError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AlarmDetails, AlarmKind, ChainEvent, ConsensusEvent, NodeEvent, PagerAlarm, PagerService,
+        Severity, SigningEvent,
+    };
+    use crate::{
+        ack::AckRequest,
+        chain_state::{Misbehavior, MisbehaviorKind},
+        cluster::SharedState,
+        consensus_monitor::{ConsensusStatus, RoundVoteStatus, ValidatorRoundVotes},
+        node_monitor::NodeStatus,
+        upgrade::UpgradePlan,
+    };
+    use std::time::{Duration, Instant};
+    use tendermint::{account, block, chain, Time};
+
+    fn event(validator: &str, missed_blocks: usize, recent_blocks: usize) -> SigningEvent {
+        SigningEvent {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            validator: validator.to_owned(),
+            moniker: None,
+            height: block::Height::from(1000_u32),
+            blocks: 100,
+            missed_blocks,
+            recent_blocks,
+            nil_votes: 0,
+            missed_proposal: None,
+            voting_power: Some(100),
+            reference_voting_power: Some(100),
+            misbehavior: None,
+        }
+    }
+
+    #[test]
+    fn names_match_serialization() {
+        for kind in [
+            AlarmKind::MissedBlocks,
+            AlarmKind::NilVotes,
+            AlarmKind::MissedProposal,
+            AlarmKind::NotInValidatorSet,
+            AlarmKind::VotingPowerChange,
+            AlarmKind::Evidence,
+            AlarmKind::NetworkEvidence,
+            AlarmKind::RoundEscalation,
+            AlarmKind::ChainHalt,
+            AlarmKind::UpgradeStalled,
+            AlarmKind::NodeUnreachable,
+            AlarmKind::NodeBehind,
+            AlarmKind::NodePeers,
+        ] {
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.as_str());
+        }
+
+        for severity in [Severity::Warning, Severity::Critical] {
+            assert_eq!(serde_json::to_value(severity).unwrap(), severity.as_str());
+        }
+    }
+
+    #[test]
+    fn alarms_are_keyed_by_validator() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+
+        pager.handle_event(event("main", 60, 0));
+        pager.handle_event(event("backup", 60, 0));
+        pager.handle_event(event("backup", 60, 5));
+
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].subject, "main");
+        assert_eq!(pager.get_alarms().len(), 1);
+    }
+
+    #[test]
+    fn alarms_on_nil_vote_ratio() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+
+        pager.handle_event(SigningEvent {
+            nil_votes: 10,
+            ..event("main", 0, 100)
+        });
+
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::NilVotes);
+
+        pager.handle_event(SigningEvent {
+            nil_votes: 9,
+            ..event("main", 0, 100)
+        });
+
+        assert!(pager.active_alarms().is_empty());
+    }
+
+    #[test]
+    fn alarms_on_validator_set_changes() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+
+        pager.handle_event(SigningEvent {
+            voting_power: Some(0),
+            ..event("main", 100, 0)
+        });
+
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::NotInValidatorSet);
+
+        pager.handle_event(SigningEvent {
+            voting_power: Some(89),
+            ..event("main", 0, 100)
+        });
+
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::VotingPowerChange);
+    }
+
+    #[test]
+    fn voting_power_changes_keep_their_reference() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+        let power = |power: u64, reference: u64| SigningEvent {
+            voting_power: Some(power),
+            reference_voting_power: Some(reference),
+            ..event("main", 0, 100)
+        };
+        let firing = |pager: &PagerService| {
+            pager
+                .active_alarms()
+                .iter()
+                .any(|alarm| alarm.kind == AlarmKind::VotingPowerChange)
+        };
+
+        pager.handle_event(power(80, 100));
+        assert!(firing(&pager));
+
+        // The chain monitor's rolling reference catching up doesn't resolve the alarm
+        pager.handle_event(power(80, 80));
+        assert!(firing(&pager));
+
+        // Returning to the original voting power does
+        pager.handle_event(power(100, 80));
+        assert!(!firing(&pager));
+        pager.handle_event(power(100, 100));
+        assert!(!firing(&pager));
+
+        // Acknowledging the alarm accepts the new voting power
+        pager.handle_event(power(50, 100));
+        assert!(firing(&pager));
+        pager
+            .ack(AckRequest {
+                chain_id: chain::Id::try_from("test-1").unwrap(),
+                kind: Some(AlarmKind::VotingPowerChange),
+                subject: None,
+                by: "alice".to_owned(),
+                note: None,
+                ttl_secs: None,
+            })
+            .unwrap();
+        pager.handle_event(power(50, 100));
+        assert!(!firing(&pager));
+        pager.handle_event(power(50, 50));
+        assert!(!firing(&pager));
+    }
+
+    #[tokio::test]
+    async fn alarms_immediately_on_evidence() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+        let notify = pager.notifier();
+
+        pager.handle_event(SigningEvent {
+            misbehavior: Some(Misbehavior {
+                kind: MisbehaviorKind::DuplicateVote,
+                validator: account::Id::new([1; 20]),
+                height: block::Height::from(10u32),
+                included_at: block::Height::from(12u32),
+            }),
+            ..event("main", 0, 100)
+        });
+
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::Evidence);
+        assert_eq!(alarms[0].severity, Severity::Critical);
+
+        // a permit is stored for the pager monitor
+        tokio::time::timeout(Duration::from_secs(1), notify.notified())
+            .await
+            .expect("not notified");
+
+        pager.handle_event(event("main", 0, 100));
+        assert!(pager.active_alarms().is_empty());
+    }
+
+    #[test]
+    fn syncs_network_evidence() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+        let chain_id = chain::Id::try_from("test-1").unwrap();
+        let alarm = |subject: &str| PagerAlarm {
+            chain_id: chain_id.clone(),
+            kind: AlarmKind::NetworkEvidence,
+            severity: Severity::Warning,
+            subject: subject.to_owned(),
+            message: "committed misbehavior".to_owned(),
+            details: AlarmDetails::default(),
+        };
+
+        pager.sync(
+            chain_id.clone(),
+            AlarmKind::NetworkEvidence,
+            vec![alarm("a"), alarm("b")],
+        );
+        assert_eq!(pager.active_alarms().len(), 2);
+
+        pager.sync(
+            chain_id.clone(),
+            AlarmKind::NetworkEvidence,
+            vec![alarm("b")],
+        );
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].subject, "b");
+    }
+
+    #[test]
+    fn alarms_on_node_status() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+        let node = |height: Option<u32>, lag: u64, peers: u64| NodeEvent {
+            status: NodeStatus {
+                name: "sentry-1".to_owned(),
+                chain_id: chain::Id::try_from("test-1").unwrap(),
+                url: "http://127.0.0.1:26657".to_owned(),
+                height: height.map(block::Height::from),
+                lag: Some(lag),
+                catching_up: false,
+                version: None,
+                peers: Some(peers),
+                last_error: None,
+                updated_at: Time::unix_epoch(),
+            },
+            max_lag: 5,
+            min_peers: 3,
+        };
+
+        pager.handle_node_event(node(None, 0, 0));
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::NodeUnreachable);
+
+        pager.handle_node_event(node(Some(100), 6, 2));
+        let kinds = pager
+            .active_alarms()
+            .iter()
+            .map(|alarm| alarm.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [AlarmKind::NodeBehind, AlarmKind::NodePeers]);
+
+        pager.handle_node_event(node(Some(105), 1, 3));
+        assert!(pager.active_alarms().is_empty());
+    }
+
+    #[test]
+    fn alarms_on_round_escalation() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+        let consensus = |round: u32| ConsensusEvent {
+            status: ConsensusStatus {
+                chain_id: chain::Id::try_from("test-1").unwrap(),
+                height: block::Height::from(100u32),
+                round,
+                step: "prevote",
+                prevotes: Some(0.5),
+                precommits: None,
+                validators: vec![ValidatorRoundVotes {
+                    name: "main".to_owned(),
+                    prevote: RoundVoteStatus::Missing,
+                    precommit: RoundVoteStatus::Missing,
+                }],
+                started_at: Time::unix_epoch(),
+                updated_at: Time::unix_epoch(),
+            },
+            max_round: 3,
+        };
+
+        pager.handle_consensus_event(consensus(2));
+        assert!(pager.active_alarms().is_empty());
+
+        pager.handle_consensus_event(consensus(3));
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::RoundEscalation);
+        assert_eq!(
+            alarms[0].message,
+            "height 100 reached round 3 (prevote step, 50% prevoted); no prevote from main"
+        );
+
+        pager.handle_consensus_event(consensus(0));
+        assert!(pager.active_alarms().is_empty());
+    }
+
+    #[test]
+    fn collects_resolutions_of_reported_alarms() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+
+        // Alarms resolved before they're reported have nothing to resolve
+        pager.handle_event(event("backup", 60, 0));
+        pager.handle_event(event("backup", 60, 5));
+        assert!(pager.get_alarms().is_empty());
+
+        pager.handle_event(event("main", 60, 0));
+        assert_eq!(pager.get_alarms().len(), 1);
+        assert!(pager.take_resolved().is_empty());
+
+        pager.handle_event(event("main", 60, 5));
+        let resolved = pager.take_resolved();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].subject, "main");
+        assert!(pager.take_resolved().is_empty());
+    }
+
+    #[test]
+    fn escalation_starts_over_after_ack_expires() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+        let key = (
+            chain::Id::try_from("test-1").unwrap(),
+            AlarmKind::MissedBlocks,
+            "main".to_owned(),
+        );
+        let hour_ago = Instant::now() - Duration::from_secs(3600);
+
+        pager.handle_event(event("main", 60, 0));
+        pager.firing_since.insert(key.clone(), hour_ago);
+        assert!(pager.get_alarms()[0].firing_for >= Duration::from_secs(3600));
+
+        // The acknowledgement expires straight away
+        let acks = pager
+            .ack(AckRequest {
+                chain_id: chain::Id::try_from("test-1").unwrap(),
+                kind: None,
+                subject: None,
+                by: "alice".to_owned(),
+                note: None,
+                ttl_secs: Some(0),
+            })
+            .unwrap();
+        assert_eq!(acks.len(), 1);
+
+        pager.firing_since.insert(key, hour_ago);
+        pager.handle_event(event("main", 60, 0));
+        let alarms = pager.get_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert!(alarms[0].firing_for < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn acknowledged_alarms_are_not_renotified() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+        let ack = |subject: Option<&str>| AckRequest {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            kind: None,
+            subject: subject.map(ToOwned::to_owned),
+            by: "alice".to_owned(),
+            note: Some("restarting".to_owned()),
+            ttl_secs: None,
+        };
+
+        pager.handle_event(event("main", 60, 0));
+        pager.handle_event(event("backup", 60, 0));
+        assert_eq!(pager.get_alarms().len(), 2);
+
+        let acks = pager.ack(ack(Some("main"))).unwrap();
+        assert_eq!(acks.len(), 1);
+        assert_eq!(acks[0].by, "alice");
+
+        pager.handle_event(event("main", 60, 0));
+        pager.handle_event(event("backup", 60, 0));
+        let alarms = pager.get_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].alarm.subject, "backup");
+        assert_eq!(pager.active_alarms().len(), 2);
+
+        // Resolving the alarm clears its acknowledgement
+        pager.handle_event(event("main", 60, 5));
+        assert!(pager.acks().is_empty());
+        pager.handle_event(event("main", 60, 0));
+        let alarms = pager.get_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].alarm.subject, "main");
+    }
+
+    #[test]
+    fn merges_peer_acks_after_failback() {
+        let mut interim = PagerService::new(50, 5, 0.1, 0.1);
+        let mut restarted = PagerService::new(50, 5, 0.1, 0.1);
+
+        for pager in [&mut interim, &mut restarted] {
+            pager.handle_event(event("main", 60, 0));
+            pager.get_alarms();
+        }
+
+        // Acknowledged on the interim leader while the other instance was down
+        interim
+            .ack(AckRequest {
+                chain_id: chain::Id::try_from("test-1").unwrap(),
+                kind: None,
+                subject: None,
+                by: "alice".to_owned(),
+                note: None,
+                ttl_secs: None,
+            })
+            .unwrap();
+
+        // Neither instance loses the acknowledgement when they merge each other's state
+        let stale = SharedState::default();
+        interim.merge_shared_state(stale);
+        restarted.merge_shared_state(SharedState {
+            acks: interim.acks(),
+            silences: vec![],
+        });
+
+        for pager in [&mut interim, &mut restarted] {
+            assert_eq!(pager.acks().len(), 1);
+            assert_eq!(pager.acks()[0].by, "alice");
+
+            // The acknowledged alarm isn't notified again by whichever instance leads
+            pager.handle_event(event("main", 60, 0));
+            assert!(pager.get_alarms().is_empty());
+        }
+    }
+
+    #[test]
+    fn suppresses_alarms_during_upgrades() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+        let chain = |halted_secs: u64, upgrade: bool| ChainEvent {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            height: Some(block::Height::from(99u32)),
+            halted_for: Duration::from_secs(halted_secs),
+            halt_after: Duration::from_secs(120),
+            upgrade: upgrade.then(|| UpgradePlan {
+                name: Some("v2".to_owned()),
+                height: block::Height::from(100u32),
+            }),
+            upgrade_resume_deadline: Duration::from_secs(1800),
+        };
+
+        pager.handle_chain_event(chain(600, false));
+        assert_eq!(pager.active_alarms()[0].kind, AlarmKind::ChainHalt);
+
+        pager.handle_chain_event(chain(600, true));
+        pager.handle_event(event("main", 60, 0));
+        assert!(pager.active_alarms().is_empty());
+
+        pager.handle_chain_event(chain(1800, true));
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::UpgradeStalled);
+        assert_eq!(alarms[0].severity, Severity::Critical);
+
+        pager.handle_chain_event(chain(0, false));
+        pager.handle_event(event("main", 60, 0));
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::MissedBlocks);
+    }
+
+    #[test]
+    fn alarms_when_no_block_is_received() {
+        let mut pager = PagerService::new(50, 5, 0.1, 0.1);
+
+        pager.handle_chain_event(ChainEvent {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            height: None,
+            halted_for: Duration::from_secs(120),
+            halt_after: Duration::from_secs(120),
+            upgrade: None,
+            upgrade_resume_deadline: Duration::from_secs(1800),
+        });
+
+        let alarms = pager.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].kind, AlarmKind::ChainHalt);
+        assert_eq!(
+            alarms[0].message,
+            "halted: no block received in 120s since startup"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Proposer selection, used to determine which validators were scheduled to
+//! propose in rounds which failed to produce a block.
+//!
+//! Mirrors CometBFT's weighted round-robin. Each increment of the proposer
+//! priorities adds every validator's voting power to its priority, selects the
+//! validator with the highest priority and subtracts the total voting power
+//! from it. The priorities returned by `/validators` for a height have already
+//! been incremented to select its round 0 proposer, so that proposer is found
+//! by replaying the increment from the previous height's validator set. Each
+//! later round applies one more increment to the stored priorities.
+
+use std::collections::BTreeMap as Map;
+use tendermint::{account, validator};
+
+/// Priorities may be at most this many times the total voting power apart.
+const PRIORITY_WINDOW_SIZE_FACTOR: i64 = 2;
+
+/// Get the proposers for rounds `0..rounds` at a height, from the validator sets at the previous
+/// height and at the height itself, as returned by `/validators`.
+///
+/// At the first height, `previous` is empty.
+pub fn round_proposers(
+    previous: &[validator::Info],
+    current: &[validator::Info],
+    rounds: u32,
+) -> Vec<account::Id> {
+    let mut proposers = Vec::with_capacity(rounds as usize);
+
+    if rounds == 0 {
+        return proposers;
+    }
+
+    let mut priorities = apply_changes(previous, current);
+
+    match increment(&mut priorities) {
+        Some(proposer) => proposers.push(proposer),
+        None => return proposers,
+    }
+
+    let mut priorities = current
+        .iter()
+        .map(|info| (info.address, (info.proposer_priority.value(), power(info))))
+        .collect::<Map<_, _>>();
+
+    for _ in 1..rounds {
+        match increment(&mut priorities) {
+            Some(proposer) => proposers.push(proposer),
+            None => break,
+        }
+    }
+
+    proposers
+}
+
+/// Priority and voting power of each validator, by address.
+type Priorities = Map<account::Id, (i64, i64)>;
+
+/// Update the previous height's validator set to the current one's validators and voting powers,
+/// as CometBFT does when applying validator updates. Added validators start with a priority
+/// below everyone else's.
+fn apply_changes(previous: &[validator::Info], current: &[validator::Info]) -> Priorities {
+    let mut priorities = previous
+        .iter()
+        .map(|info| (info.address, (info.proposer_priority.value(), power(info))))
+        .collect::<Priorities>();
+
+    let unchanged = priorities.len() == current.len()
+        && current.iter().all(|info| {
+            priorities
+                .get(&info.address)
+                .is_some_and(|(_, power)| *power == self::power(info))
+        });
+
+    if unchanged {
+        return priorities;
+    }
+
+    // Total voting power after the updates, but before removing validators
+    let removed_power = priorities
+        .iter()
+        .filter(|(address, _)| current.iter().all(|info| info.address != **address))
+        .map(|(_, (_, power))| power)
+        .sum::<i64>();
+    let total_power = current.iter().map(power).sum::<i64>() + removed_power;
+    let new_priority = -(total_power + (total_power >> 3));
+
+    priorities = current
+        .iter()
+        .map(|info| {
+            let priority = priorities
+                .get(&info.address)
+                .map_or(new_priority, |(priority, _)| *priority);
+
+            (info.address, (priority, power(info)))
+        })
+        .collect();
+
+    rescale_and_center(&mut priorities);
+    priorities
+}
+
+/// Increment the proposer priorities once, returning the selected proposer.
+fn increment(priorities: &mut Priorities) -> Option<account::Id> {
+    rescale_and_center(priorities);
+
+    for (priority, power) in priorities.values_mut() {
+        *priority = priority.saturating_add(*power);
+    }
+
+    let total_power = priorities.values().map(|(_, power)| power).sum::<i64>();
+
+    // Ties are broken in favor of the lowest address, which comes first
+    let (address, (priority, _)) =
+        priorities
+            .iter_mut()
+            .reduce(|a, b| if b.1 .0 > a.1 .0 { b } else { a })?;
+
+    *priority = priority.saturating_sub(total_power);
+    Some(*address)
+}
+
+/// Scale priorities down to within the priority window of each other, then center them on zero.
+fn rescale_and_center(priorities: &mut Priorities) {
+    let total_power = priorities.values().map(|(_, power)| power).sum::<i64>();
+    let max_diff = PRIORITY_WINDOW_SIZE_FACTOR * total_power;
+    let max = priorities.values().map(|(priority, _)| *priority).max();
+    let min = priorities.values().map(|(priority, _)| *priority).min();
+
+    let (Some(max), Some(min)) = (max, min) else {
+        return;
+    };
+
+    let diff = max.saturating_sub(min);
+
+    if max_diff > 0 && diff > max_diff {
+        let ratio = (diff + max_diff - 1) / max_diff;
+
+        for (priority, _) in priorities.values_mut() {
+            *priority /= ratio;
+        }
+    }
+
+    let sum = priorities
+        .values()
+        .map(|(priority, _)| i128::from(*priority))
+        .sum::<i128>();
+    let average = sum.div_euclid(priorities.len() as i128) as i64;
+
+    for (priority, _) in priorities.values_mut() {
+        *priority = priority.saturating_sub(average);
+    }
+}
+
+fn power(info: &validator::Info) -> i64 {
+    info.power() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::round_proposers;
+    use tendermint::{account, validator, vote, PublicKey};
+
+    fn validator(byte: u8, power: u32, priority: i64) -> validator::Info {
+        let pub_key = PublicKey::from_raw_ed25519(&[byte; 32]).unwrap();
+
+        validator::Info {
+            address: account::Id::new([byte; 20]),
+            pub_key,
+            power: vote::Power::from(power),
+            name: None,
+            proposer_priority: priority.into(),
+        }
+    }
+
+    #[test]
+    fn selects_round_0_proposer_from_previous_height() {
+        // Two validators with equal power: the previous height's [0, 0] are incremented to
+        // [1, 1], the tie goes to the lowest address, and [-1, 1] is stored for this height
+        let previous = [validator(1, 1, 0), validator(2, 1, 0)];
+        let current = [validator(1, 1, -1), validator(2, 1, 1)];
+        let proposers = round_proposers(&previous, &current, 3);
+
+        assert_eq!(
+            proposers,
+            [1, 2, 1].map(|byte| account::Id::new([byte; 20]))
+        );
+    }
+
+    #[test]
+    fn matches_cometbft_proposer_sequence() {
+        // Sequence from CometBFT's `TestProposerSelection1`, for a genesis validator set of
+        // bar (300), baz (330) and foo (1000). Genesis priorities are stored after the first
+        // increment selects foo.
+        let (bar, baz, foo) = (1, 2, 3);
+        let genesis = [
+            validator(foo, 1000, -630),
+            validator(bar, 300, 300),
+            validator(baz, 330, 330),
+        ];
+        let expected = "foo baz foo bar foo foo baz foo bar foo foo baz foo foo bar foo baz foo foo \
+            bar foo foo baz foo bar foo foo baz foo bar foo foo baz foo foo bar foo baz foo foo bar \
+            foo baz foo foo bar foo baz foo foo bar foo baz foo foo foo baz bar foo foo foo baz foo \
+            bar foo foo baz foo bar foo foo baz foo bar foo foo baz foo bar foo foo baz foo foo bar \
+            foo baz foo foo bar foo baz foo foo bar foo baz foo foo"
+            .split_whitespace()
+            .map(|name| match name {
+                "bar" => account::Id::new([bar; 20]),
+                "baz" => account::Id::new([baz; 20]),
+                _ => account::Id::new([foo; 20]),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(round_proposers(&[], &genesis, 99), expected);
+    }
+
+    #[test]
+    fn added_validators_start_with_lowest_priority() {
+        // A validator joining with the most power still isn't selected straight away
+        let previous = [validator(1, 10, 0), validator(2, 10, 0)];
+        let current = [
+            validator(1, 10, 0),
+            validator(2, 10, 0),
+            validator(3, 30, 0),
+        ];
+
+        assert_eq!(
+            round_proposers(&previous, &current, 1),
+            [account::Id::new([1; 20])]
+        );
+    }
+}
@@ -0,0 +1,391 @@
+//! Alarm routing: decides which sinks each alarm is sent to.
+//!
+//! Routes are evaluated in order. Alarms are sent to the sinks of the first matching route, and
+//! of any later matching routes as long as the routes before them set `continue = true`. When no
+//! routes are configured every alarm is sent to every sink.
+//!
+//! Routes may also have escalation steps, which send alarms to additional sinks once they've been
+//! firing for long enough.
+
+use crate::{
+    config::{ObservatoryConfig, RouteConfig},
+    error::{Error, ErrorKind},
+    pager::PagerAlarm,
+    sink::Sink,
+};
+use std::{collections::BTreeMap as Map, time::Duration};
+use tendermint::chain;
+
+/// Alarm router
+#[derive(Debug, Default)]
+pub struct Router {
+    /// Configured sinks.
+    sinks: Vec<Sink>,
+
+    /// Routing rules.
+    routes: Vec<Route>,
+
+    /// Tags of each chain.
+    tags: Map<chain::Id, Map<String, String>>,
+}
+
+impl Router {
+    /// Create a router from the configured sinks and routes.
+    ///
+    /// If no sinks are configured, alarms are sent to Datadog as long as `[datadog]` has an API
+    /// key.
+    pub fn new(config: &ObservatoryConfig) -> Result<Self, Error> {
+        let sinks = if config.sinks.is_empty() {
+            config
+                .datadog
+                .as_ref()
+                .and_then(|datadog| datadog.dd_api_key.clone())
+                .map(Sink::datadog)
+                .into_iter()
+                .collect()
+        } else {
+            config
+                .sinks
+                .iter()
+                .map(|sink| Sink::new(sink, config.datadog.as_ref()))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        for (i, sink) in sinks.iter().enumerate() {
+            if sinks[..i].iter().any(|other| other.name() == sink.name()) {
+                return Err(config_error(format!(
+                    "sink '{}' is configured more than once",
+                    sink.name()
+                )));
+            }
+        }
+
+        let find_sinks = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| {
+                    sinks
+                        .iter()
+                        .position(|sink| sink.name() == name)
+                        .ok_or_else(|| {
+                            config_error(format!("route refers to unknown sink '{name}'"))
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let routes = config
+            .routes
+            .iter()
+            .map(|route| {
+                Ok(Route {
+                    config: route.clone(),
+                    sinks: find_sinks(&route.sinks)?,
+                    escalations: route
+                        .escalations
+                        .iter()
+                        .map(|step| Ok((step.after(), find_sinks(&step.sinks)?)))
+                        .collect::<Result<Vec<_>, Error>>()?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let tags = config
+            .chains
+            .iter()
+            .map(|chain| (chain.id.clone(), chain.tags.clone()))
+            .collect();
+
+        Ok(Self {
+            sinks,
+            routes,
+            tags,
+        })
+    }
+
+    /// Are any sinks configured?
+    pub fn has_sinks(&self) -> bool {
+        !self.sinks.is_empty()
+    }
+
+    /// Get the configured sinks.
+    pub fn sinks(&self) -> &[Sink] {
+        &self.sinks
+    }
+
+    /// Get the sinks the given alarm should be sent to, having been firing for `firing_for`.
+    pub fn route(&self, alarm: &PagerAlarm, firing_for: Duration) -> Vec<&Sink> {
+        if self.routes.is_empty() {
+            return self.sinks.iter().collect();
+        }
+
+        let mut indexes = Vec::new();
+
+        for route in self.matching_routes(alarm) {
+            let escalated = route
+                .escalations
+                .iter()
+                .filter(|(after, _)| *after <= firing_for)
+                .flat_map(|(_, sinks)| sinks);
+
+            for index in route.sinks.iter().chain(escalated) {
+                if !indexes.contains(index) {
+                    indexes.push(*index);
+                }
+            }
+        }
+
+        indexes
+            .into_iter()
+            .map(|index| &self.sinks[index])
+            .collect()
+    }
+
+    /// Get the amount of time until the given alarm, having been firing for `firing_for`, reaches
+    /// its next escalation step (if it has one).
+    pub fn next_escalation(&self, alarm: &PagerAlarm, firing_for: Duration) -> Option<Duration> {
+        self.matching_routes(alarm)
+            .into_iter()
+            .flat_map(|route| &route.escalations)
+            .filter(|(after, _)| *after > firing_for)
+            .map(|(after, _)| *after - firing_for)
+            .min()
+    }
+
+    /// Get the routes which match the given alarm, stopping at the first which doesn't continue.
+    fn matching_routes(&self, alarm: &PagerAlarm) -> Vec<&Route> {
+        let tags = self.tags.get(&alarm.chain_id);
+        let mut routes = Vec::new();
+
+        for route in &self.routes {
+            if !route.matches(alarm, tags) {
+                continue;
+            }
+
+            routes.push(route);
+
+            if !route.config.continue_matching {
+                break;
+            }
+        }
+
+        routes
+    }
+}
+
+/// Routing rule, with sinks resolved to their indexes.
+#[derive(Debug)]
+struct Route {
+    /// Route configuration.
+    config: RouteConfig,
+
+    /// Sinks matching alarms are sent to.
+    sinks: Vec<usize>,
+
+    /// Sinks matching alarms are also sent to once they've been firing for a while.
+    escalations: Vec<(Duration, Vec<usize>)>,
+}
+
+impl Route {
+    /// Does the route match the alarm, given the tags of its chain?
+    fn matches(&self, alarm: &PagerAlarm, tags: Option<&Map<String, String>>) -> bool {
+        let route = &self.config;
+
+        (route.chain_ids.is_empty() || route.chain_ids.contains(&alarm.chain_id))
+            && (route.kinds.is_empty() || route.kinds.contains(&alarm.kind))
+            && route
+                .severity
+                .is_none_or(|severity| severity == alarm.severity)
+            && route.tags.iter().all(|(key, value)| {
+                tags.and_then(|tags| tags.get(key))
+                    .is_some_and(|tag| tag == value)
+            })
+    }
+}
+
+fn config_error(message: String) -> Error {
+    ErrorKind::Config.context(message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Router;
+    use crate::{
+        config::ObservatoryConfig,
+        pager::{AlarmDetails, AlarmKind, PagerAlarm, Severity},
+    };
+    use std::time::Duration;
+    use tendermint::chain;
+
+    fn alarm(chain_id: &str, kind: AlarmKind, severity: Severity) -> PagerAlarm {
+        PagerAlarm {
+            chain_id: chain::Id::try_from(chain_id).unwrap(),
+            kind,
+            severity,
+            subject: "main".to_owned(),
+            message: "something is wrong".to_owned(),
+            details: AlarmDetails::default(),
+        }
+    }
+
+    #[test]
+    fn routes_by_tags_and_severity() {
+        let config: ObservatoryConfig = toml::from_str(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            rpc_urls = []
+            tags = { tier = "mainnet" }
+
+            [[chain]]
+            id = "theta-testnet-001"
+            rpc_urls = []
+            tags = { tier = "testnet" }
+
+            [[sink]]
+            name = "slack"
+            type = "slack"
+            webhook_url = "https://hooks.slack.com/services/x"
+
+            [[sink]]
+            name = "pagerduty"
+            type = "pagerduty"
+            routing_key = "key"
+
+            [[route]]
+            tags = { tier = "mainnet" }
+            sinks = ["slack"]
+            continue = true
+
+            [[route]]
+            tags = { tier = "mainnet" }
+            severity = "critical"
+            sinks = ["pagerduty"]
+
+            [[route]]
+            kinds = ["chain_halt"]
+            sinks = ["pagerduty"]
+
+            [[route]]
+            sinks = ["slack"]
+            "#,
+        )
+        .unwrap();
+
+        let router = Router::new(&config).unwrap();
+        let route = |alarm: PagerAlarm| {
+            router
+                .route(&alarm, Duration::ZERO)
+                .iter()
+                .map(|sink| sink.name().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            route(alarm(
+                "cosmoshub-4",
+                AlarmKind::MissedBlocks,
+                Severity::Critical
+            )),
+            ["slack", "pagerduty"]
+        );
+        assert_eq!(
+            route(alarm(
+                "cosmoshub-4",
+                AlarmKind::NodePeers,
+                Severity::Warning
+            )),
+            ["slack"]
+        );
+        assert_eq!(
+            route(alarm(
+                "theta-testnet-001",
+                AlarmKind::MissedBlocks,
+                Severity::Critical
+            )),
+            ["slack"]
+        );
+        assert_eq!(
+            route(alarm(
+                "theta-testnet-001",
+                AlarmKind::ChainHalt,
+                Severity::Critical
+            )),
+            ["pagerduty"]
+        );
+    }
+
+    #[test]
+    fn escalates_long_firing_alarms() {
+        let config: ObservatoryConfig = toml::from_str(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            rpc_urls = []
+
+            [[sink]]
+            name = "slack"
+            type = "slack"
+            webhook_url = "https://hooks.slack.com/services/x"
+
+            [[sink]]
+            name = "pagerduty"
+            type = "pagerduty"
+            routing_key = "primary"
+
+            [[sink]]
+            name = "secondary"
+            type = "pagerduty"
+            routing_key = "secondary"
+
+            [[route]]
+            sinks = ["slack"]
+
+            [[route.escalation]]
+            after_secs = 300
+            sinks = ["pagerduty"]
+
+            [[route.escalation]]
+            after_secs = 1200
+            sinks = ["secondary"]
+            "#,
+        )
+        .unwrap();
+
+        let router = Router::new(&config).unwrap();
+        let alarm = alarm("cosmoshub-4", AlarmKind::ChainHalt, Severity::Critical);
+        let route = |secs: u64| {
+            router
+                .route(&alarm, Duration::from_secs(secs))
+                .iter()
+                .map(|sink| sink.name().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(route(0), ["slack"]);
+        assert_eq!(route(300), ["slack", "pagerduty"]);
+        assert_eq!(route(1500), ["slack", "pagerduty", "secondary"]);
+
+        let next_escalation = |secs: u64| router.next_escalation(&alarm, Duration::from_secs(secs));
+        assert_eq!(next_escalation(60), Some(Duration::from_secs(240)));
+        assert_eq!(next_escalation(300), Some(Duration::from_secs(900)));
+        assert_eq!(next_escalation(1500), None);
+    }
+
+    #[test]
+    fn rejects_unknown_sinks() {
+        let config: ObservatoryConfig = toml::from_str(
+            r#"
+            [[chain]]
+            id = "cosmoshub-4"
+            rpc_urls = []
+
+            [[route]]
+            sinks = ["slack"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(Router::new(&config).is_err());
+    }
+}
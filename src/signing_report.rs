@@ -0,0 +1,269 @@
+//! Signing statistics for every validator in a chain's validator set.
+//!
+//! Comparing our validators against the rest of the set helps distinguish
+//! network-wide degradation (e.g. a bad release) from a problem specific to us.
+
+use crate::{
+    chain_state::{ChainState, VoteCounts},
+    config::ValidatorConfig,
+};
+use serde::Serialize;
+use std::{collections::BTreeMap as Map, sync::Arc};
+use tendermint::{account, block, chain, validator};
+
+/// Snapshot of the data a signing report is computed from.
+///
+/// Taking a snapshot is cheap, so one is taken every block while the report itself is only
+/// computed when it's requested.
+#[derive(Clone, Debug)]
+pub struct SigningSnapshot {
+    /// Chain state, sharing its blocks with the chain monitor.
+    chain_state: ChainState,
+
+    /// Latest block height.
+    height: block::Height,
+
+    /// Current validator set.
+    validator_set: Arc<[validator::Info]>,
+
+    /// Validators we monitor.
+    monitored: Vec<ValidatorConfig>,
+}
+
+impl SigningSnapshot {
+    /// Take a snapshot of the given chain state and validator set.
+    pub fn new(
+        chain_state: &ChainState,
+        height: block::Height,
+        validator_set: Arc<[validator::Info]>,
+        monitored: &[ValidatorConfig],
+    ) -> Self {
+        Self {
+            chain_state: chain_state.clone(),
+            height,
+            validator_set,
+            monitored: monitored.to_vec(),
+        }
+    }
+
+    /// Compute the signing report.
+    pub fn report(&self) -> SigningReport {
+        SigningReport::new(
+            self.chain_state.chain_id().clone(),
+            self.height,
+            self.chain_state.commit_count(),
+            &self.chain_state.vote_counts(),
+            &self.validator_set,
+            &self.monitored,
+        )
+    }
+}
+
+/// Signing report for a chain, ranking validators by missed blocks.
+#[derive(Clone, Debug, Serialize)]
+pub struct SigningReport {
+    /// Chain ID.
+    pub chain_id: chain::Id,
+
+    /// Latest block height included in the report.
+    pub height: block::Height,
+
+    /// Number of blocks with a last commit in the history window.
+    pub blocks: usize,
+
+    /// Mean uptime across all validators in the set.
+    pub average_uptime: Option<f64>,
+
+    /// Validators ranked by missed blocks, then voting power.
+    pub validators: Vec<ValidatorSigningStats>,
+}
+
+impl SigningReport {
+    /// Compute signing stats for every member of the given validator set from the votes counted
+    /// over `blocks` blocks with a last commit.
+    ///
+    /// Validators are evaluated against every block in the history window, so
+    /// those which recently joined the set will be counted as having missed blocks.
+    pub fn new(
+        chain_id: chain::Id,
+        height: block::Height,
+        blocks: usize,
+        vote_counts: &Map<account::Id, VoteCounts>,
+        validator_set: &[validator::Info],
+        monitored: &[ValidatorConfig],
+    ) -> Self {
+        let mut validators = validator_set
+            .iter()
+            .map(|info| {
+                let votes = vote_counts.get(&info.address).copied().unwrap_or_default();
+                let signed_blocks = votes.commit + votes.nil;
+
+                ValidatorSigningStats {
+                    address: info.address,
+                    moniker: info.name.clone(),
+                    monitored: monitored
+                        .iter()
+                        .find(|validator| validator.addr == info.address)
+                        .map(|validator| validator.name.clone()),
+                    voting_power: info.power(),
+                    signed_blocks,
+                    nil_votes: votes.nil,
+                    proposed_blocks: votes.proposed,
+                    missed_blocks: blocks.saturating_sub(signed_blocks),
+                    uptime: (blocks > 0).then(|| signed_blocks as f64 * 100.0 / blocks as f64),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        validators.sort_by(|a, b| {
+            b.missed_blocks
+                .cmp(&a.missed_blocks)
+                .then(b.voting_power.cmp(&a.voting_power))
+        });
+
+        let uptimes = validators
+            .iter()
+            .filter_map(|stats| stats.uptime)
+            .collect::<Vec<_>>();
+
+        Self {
+            chain_id,
+            height,
+            blocks,
+            average_uptime: (!uptimes.is_empty())
+                .then(|| uptimes.iter().sum::<f64>() / uptimes.len() as f64),
+            validators,
+        }
+    }
+
+    /// Rank (starting at 1) of the given validator in this report.
+    pub fn rank(&self, address: account::Id) -> Option<usize> {
+        self.validators
+            .iter()
+            .position(|stats| stats.address == address)
+            .map(|index| index + 1)
+    }
+}
+
+/// Signing stats for an individual validator.
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorSigningStats {
+    /// Validator consensus address.
+    pub address: account::Id,
+
+    /// Validator name reported by `/validators`, if available.
+    pub moniker: Option<String>,
+
+    /// Name of this validator in our config if it's one we monitor.
+    pub monitored: Option<String>,
+
+    /// Voting power.
+    pub voting_power: u64,
+
+    /// Number of blocks in the history window this validator signed.
+    pub signed_blocks: usize,
+
+    /// Number of signed blocks where this validator precommitted nil.
+    pub nil_votes: usize,
+
+    /// Number of blocks in the history window this validator proposed.
+    pub proposed_blocks: usize,
+
+    /// Number of blocks in the history window missing this validator's signature.
+    pub missed_blocks: usize,
+
+    /// Percentage of blocks in the history window this validator signed.
+    pub uptime: Option<f64>,
+}
+
+impl ValidatorSigningStats {
+    /// Name to display for this validator.
+    pub fn display_name(&self) -> String {
+        self.monitored
+            .as_ref()
+            .or(self.moniker.as_ref())
+            .cloned()
+            .unwrap_or_else(|| self.address.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SigningReport;
+    use crate::{chain_state::VoteCounts, config::ValidatorConfig};
+    use tendermint::{account, block, chain, validator, vote, PublicKey};
+
+    fn validator(byte: u8, power: u32) -> validator::Info {
+        validator::Info {
+            address: account::Id::new([byte; 20]),
+            pub_key: PublicKey::from_raw_ed25519(&[byte; 32]).unwrap(),
+            power: vote::Power::from(power),
+            name: Some(format!("validator-{byte}")),
+            proposer_priority: 0.into(),
+        }
+    }
+
+    fn votes(commit: usize, nil: usize, proposed: usize) -> VoteCounts {
+        VoteCounts {
+            commit,
+            nil,
+            proposed,
+        }
+    }
+
+    #[test]
+    fn ranks_validators_by_missed_blocks_then_power() {
+        let validator_set = [
+            validator(1, 100),
+            validator(2, 50),
+            validator(3, 10),
+            validator(4, 80),
+        ];
+        let vote_counts = [
+            (validator_set[0].address, votes(10, 0, 4)),
+            (validator_set[1].address, votes(6, 2, 3)),
+            (validator_set[2].address, votes(4, 4, 1)),
+        ]
+        .into_iter()
+        .collect();
+        let monitored = [ValidatorConfig {
+            name: "ours".to_owned(),
+            addr: validator_set[2].address,
+        }];
+
+        let report = SigningReport::new(
+            chain::Id::try_from("test-1").unwrap(),
+            block::Height::from(100_u32),
+            10,
+            &vote_counts,
+            &validator_set,
+            &monitored,
+        );
+
+        let ranking = report
+            .validators
+            .iter()
+            .map(|stats| {
+                (
+                    stats.display_name(),
+                    stats.missed_blocks,
+                    stats.nil_votes,
+                    stats.proposed_blocks,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            ranking,
+            [
+                ("validator-4".to_owned(), 10, 0, 0),
+                ("validator-2".to_owned(), 2, 2, 3),
+                ("ours".to_owned(), 2, 4, 1),
+                ("validator-1".to_owned(), 0, 0, 4),
+            ]
+        );
+        assert_eq!(report.rank(validator_set[2].address), Some(3));
+        assert_eq!(report.validators[2].uptime, Some(80.0));
+        assert_eq!(report.average_uptime, Some(65.0));
+    }
+}
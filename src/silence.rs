@@ -0,0 +1,417 @@
+//! Silences suppress notifications for a chain (or one of its validators) during a maintenance
+//! window. Alarm state keeps being tracked while silenced, so anything still firing when the
+//! silence ends is reported as usual.
+//!
+//! Silences are either defined in the config file or added at runtime through the API, in which
+//! case they're persisted to disk so they survive restarts.
+
+use crate::{
+    config::SilenceConfig,
+    error::{Error, ErrorKind},
+    pager::PagerAlarm,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf, process};
+use tendermint::{chain, Time};
+use tracing::warn;
+
+/// Silence
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Silence {
+    /// Unique ID of the silence, used to remove it.
+    pub id: String,
+
+    /// Chain ID the silence applies to.
+    pub chain_id: chain::Id,
+
+    /// Validator (or node) the silence applies to, or every alarm on the chain if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator: Option<String>,
+
+    /// Time the silence starts.
+    pub start: Time,
+
+    /// Time the silence ends.
+    pub end: Time,
+
+    /// Why the alarms are silenced.
+    pub reason: String,
+
+    /// Time the silence was removed, if it was. Removed silences are kept until they would have
+    /// ended, so other instances don't restore them from stale copies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<Time>,
+}
+
+impl Silence {
+    /// Create a new silence from its configuration, starting now if no start time is given.
+    fn new(id: String, config: &SilenceConfig) -> Result<Self, Error> {
+        if let Some(start) = config.start
+            && config.end <= start
+        {
+            return Err(ErrorKind::Config
+                .context(format!(
+                    "silence for {} ends at {} before it starts at {}",
+                    config.chain_id, config.end, start
+                ))
+                .into());
+        }
+
+        let start = config.start.unwrap_or_else(Time::now);
+
+        Ok(Self {
+            id,
+            chain_id: config.chain_id.clone(),
+            validator: config.validator.clone(),
+            start,
+            end: config.end,
+            reason: config.reason.clone(),
+            removed: None,
+        })
+    }
+
+    /// Is the silence in effect at the given time?
+    pub fn is_active(&self, now: Time) -> bool {
+        self.removed.is_none() && self.start <= now && now < self.end
+    }
+
+    /// Does the silence apply to the given alarm?
+    pub fn matches(&self, alarm: &PagerAlarm) -> bool {
+        self.chain_id == alarm.chain_id
+            && self
+                .validator
+                .as_ref()
+                .is_none_or(|validator| validator == &alarm.subject)
+    }
+}
+
+/// Configured and runtime silences.
+#[derive(Debug, Default)]
+pub struct Silences {
+    /// Silences defined in the config file, which can't be removed at runtime.
+    configured: Vec<Silence>,
+
+    /// Silences added at runtime, including removed ones which haven't ended yet.
+    runtime: Vec<Silence>,
+
+    /// File runtime silences are persisted to.
+    path: Option<PathBuf>,
+
+    /// Prefix of the IDs of silences added at runtime, unique to this instance when clustered.
+    id_prefix: Option<String>,
+}
+
+impl Silences {
+    /// Load silences from the config file and the file runtime silences are persisted to.
+    ///
+    /// Configured silences which have already ended are ignored.
+    pub fn load(configured: &[SilenceConfig], path: Option<PathBuf>) -> Result<Self, Error> {
+        let now = Time::now();
+        let mut silences = Vec::with_capacity(configured.len());
+
+        for (i, config) in configured.iter().enumerate() {
+            let silence = Silence::new(format!("config-{}", i + 1), config)?;
+
+            if silence.end <= now {
+                warn!(
+                    "ignoring silence {} for {}: it ended at {}",
+                    silence.id, silence.chain_id, silence.end
+                );
+                continue;
+            }
+
+            silences.push(silence);
+        }
+
+        let runtime = match &path {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(json) => serde_json::from_str(&json).map_err(|err| {
+                    ErrorKind::Config.context(format!("invalid {}: {err}", path.display()))
+                })?,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+                Err(err) => return Err(err.into()),
+            },
+            None => vec![],
+        };
+
+        Ok(Self {
+            configured: silences,
+            runtime,
+            path,
+            id_prefix: None,
+        })
+    }
+
+    /// Prefix the IDs of silences added at runtime, e.g. `us-east-1`, so they're unique across a
+    /// cluster.
+    pub fn with_id_prefix(mut self, prefix: String) -> Self {
+        self.id_prefix = Some(prefix);
+        self
+    }
+
+    /// Add a silence at runtime, persisting it to disk.
+    ///
+    /// Fails if `silences_path` isn't configured, as the silence would be lost on restart.
+    pub fn add(&mut self, config: &SilenceConfig) -> Result<Silence, Error> {
+        if self.path.is_none() {
+            return Err(ErrorKind::Config
+                .context("silences can't be added at runtime unless silences_path is configured")
+                .into());
+        }
+
+        if config.end <= Time::now() {
+            return Err(ErrorKind::Config
+                .context(format!(
+                    "silence for {} ends at {}, which has already passed",
+                    config.chain_id, config.end
+                ))
+                .into());
+        }
+
+        let prefix = self
+            .id_prefix
+            .as_ref()
+            .map(|prefix| format!("{prefix}-"))
+            .unwrap_or_default();
+
+        let number = self
+            .runtime
+            .iter()
+            .filter_map(|silence| silence.id.strip_prefix(&prefix)?.parse::<u64>().ok())
+            .max()
+            .unwrap_or_default()
+            + 1;
+
+        let silence = Silence::new(format!("{prefix}{number}"), config)?;
+        self.runtime.push(silence.clone());
+        self.save()?;
+        Ok(silence)
+    }
+
+    /// Remove a silence added at runtime, returning it if it existed.
+    pub fn remove(&mut self, id: &str) -> Result<Option<Silence>, Error> {
+        if self.configured.iter().any(|silence| silence.id == id) {
+            return Err(ErrorKind::Config
+                .context(format!(
+                    "silence {id} is defined in the config file and can't be removed"
+                ))
+                .into());
+        }
+
+        let Some(silence) = self
+            .runtime
+            .iter_mut()
+            .find(|silence| silence.id == id && silence.removed.is_none())
+        else {
+            return Ok(None);
+        };
+
+        silence.removed = Some(Time::now());
+        let silence = silence.clone();
+        self.save()?;
+        Ok(Some(silence))
+    }
+
+    /// List all silences which haven't been removed, including ones which have ended.
+    pub fn list(&self) -> Vec<Silence> {
+        self.configured
+            .iter()
+            .chain(&self.runtime)
+            .filter(|silence| silence.removed.is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// List silences added at runtime, including removed ones which haven't ended yet.
+    pub fn runtime(&self) -> &[Silence] {
+        &self.runtime
+    }
+
+    /// Merge another instance's runtime silences by ID, persisting them if anything changed.
+    /// A silence removed on either instance stays removed.
+    pub fn merge(&mut self, runtime: Vec<Silence>) -> Result<(), Error> {
+        let now = Time::now();
+        let mut changed = false;
+
+        for silence in runtime.into_iter().filter(|silence| silence.end > now) {
+            match self
+                .runtime
+                .iter_mut()
+                .find(|existing| existing.id == silence.id)
+            {
+                Some(existing) => {
+                    if existing.removed.is_none() && silence.removed.is_some() {
+                        existing.removed = silence.removed;
+                        changed = true;
+                    }
+                }
+                None => {
+                    self.runtime.push(silence);
+                    changed = true;
+                }
+            }
+        }
+
+        if changed {
+            self.save()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Find the silence in effect for the given alarm at the given time, if any.
+    pub fn silencing(&self, alarm: &PagerAlarm, now: Time) -> Option<&Silence> {
+        self.configured
+            .iter()
+            .chain(&self.runtime)
+            .find(|silence| silence.is_active(now) && silence.matches(alarm))
+    }
+
+    /// Persist runtime silences which haven't ended yet.
+    fn save(&mut self) -> Result<(), Error> {
+        let now = Time::now();
+        self.runtime.retain(|silence| silence.end > now);
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let json =
+            serde_json::to_string_pretty(&self.runtime).expect("couldn't serialize silences");
+
+        // Write to a temporary file next to it first so a crash can't leave a truncated file
+        // behind, named after this process so instances sharing the file don't collide
+        let mut tmp_name = path.file_name().unwrap_or_default().to_owned();
+        tmp_name.push(format!(".{}.tmp", process::id()));
+        let tmp_path = path.with_file_name(tmp_name);
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Silences;
+    use crate::{
+        config::SilenceConfig,
+        pager::{AlarmDetails, AlarmKind, PagerAlarm, Severity},
+    };
+    use std::time::Duration;
+    use tendermint::{chain, Time};
+
+    fn alarm(subject: &str) -> PagerAlarm {
+        PagerAlarm {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            kind: AlarmKind::MissedBlocks,
+            severity: Severity::Critical,
+            subject: subject.to_owned(),
+            message: "missed 60 of the last 100 blocks".to_owned(),
+            details: AlarmDetails::default(),
+        }
+    }
+
+    #[test]
+    fn persists_runtime_silences() {
+        let path =
+            std::env::temp_dir().join(format!("observatory-silences-{}.json", std::process::id()));
+        let now = Time::now();
+        let config = SilenceConfig {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            validator: Some("main".to_owned()),
+            start: None,
+            end: (now + Duration::from_secs(3600)).unwrap(),
+            reason: "restarting".to_owned(),
+        };
+
+        let mut silences = Silences::load(&[], Some(path.clone())).unwrap();
+        let silence = silences.add(&config).unwrap();
+        assert_eq!(silence.id, "1");
+        assert!(silences.silencing(&alarm("main"), Time::now()).is_some());
+        assert!(silences.silencing(&alarm("backup"), Time::now()).is_none());
+
+        let mut silences = Silences::load(&[config], Some(path.clone())).unwrap();
+        assert_eq!(silences.list().len(), 2);
+        assert!(silences.remove("config-1").is_err());
+        assert!(silences.remove("1").unwrap().is_some());
+        assert!(Silences::load(&[], Some(path.clone()))
+            .unwrap()
+            .list()
+            .is_empty());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn merges_silences_after_failover_and_failback() {
+        let path = |instance: &str| {
+            std::env::temp_dir().join(format!(
+                "observatory-silences-{instance}-{}.json",
+                std::process::id()
+            ))
+        };
+        let load = |instance: &str| {
+            Silences::load(&[], Some(path(instance)))
+                .unwrap()
+                .with_id_prefix(instance.to_owned())
+        };
+        let config = SilenceConfig {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            validator: None,
+            start: None,
+            end: (Time::now() + Duration::from_secs(3600)).unwrap(),
+            reason: "upgrading".to_owned(),
+        };
+        let ids = |silences: &Silences| {
+            silences
+                .list()
+                .into_iter()
+                .map(|silence| silence.id)
+                .collect::<Vec<_>>()
+        };
+
+        // The leader adds a silence, which its follower merges
+        let mut east = load("east");
+        let mut west = load("west");
+        assert_eq!(east.add(&config).unwrap().id, "east-1");
+        west.merge(east.runtime().to_vec()).unwrap();
+
+        // While the leader is down, the follower takes over, removes it and adds another
+        let stale = east.runtime().to_vec();
+        drop(east);
+        assert!(west.remove("east-1").unwrap().is_some());
+        assert_eq!(west.add(&config).unwrap().id, "west-1");
+
+        // When the leader restarts with its stale file, neither loses the interim changes
+        let mut east = load("east");
+        east.merge(west.runtime().to_vec()).unwrap();
+        west.merge(stale).unwrap();
+        assert_eq!(ids(&east), ["west-1"]);
+        assert_eq!(ids(&west), ["west-1"]);
+        assert!(east.silencing(&alarm("main"), Time::now()).is_some());
+
+        std::fs::remove_file(path("east")).unwrap();
+        std::fs::remove_file(path("west")).unwrap();
+    }
+
+    #[test]
+    fn ignores_expired_silences() {
+        let config = SilenceConfig {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            validator: None,
+            start: None,
+            end: (Time::now() - Duration::from_secs(60)).unwrap(),
+            reason: "done".to_owned(),
+        };
+
+        let mut silences = Silences::load(std::slice::from_ref(&config), None).unwrap();
+        assert!(silences.list().is_empty());
+
+        // Runtime silences can't be added without somewhere to persist them
+        let config = SilenceConfig {
+            end: (Time::now() + Duration::from_secs(60)).unwrap(),
+            ..config
+        };
+        assert!(silences.add(&config).is_err());
+    }
+}
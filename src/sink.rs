@@ -0,0 +1,536 @@
+//! Sinks alarms are sent to: Datadog events, Slack incoming webhooks and PagerDuty.
+//!
+//! Each alarm is rendered with the sink's title and body templates (see [`crate::template`]),
+//! which default to ones suited to the sink's type.
+
+use crate::{
+    config::{DataDogConfig, RateLimitConfig, SinkConfig, SinkType},
+    datadog::{self, send_stream_event, StreamEvent},
+    error::{Error, ErrorKind},
+    http,
+    pager::{AlarmKey, PagerAlarm, Severity},
+    template::{self, Context, Links},
+};
+use hyper::Method;
+use serde_json::json;
+use std::{
+    collections::{BTreeMap as Map, BTreeSet as Set},
+    time::SystemTime,
+};
+use tendermint::chain;
+
+/// PagerDuty Events API v2 endpoint.
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// PagerDuty Events API v2 change event endpoint.
+const PAGERDUTY_CHANGE_EVENTS_URL: &str = "https://events.pagerduty.com/v2/change/enqueue";
+
+/// Default title template.
+const DEFAULT_TITLE: &str = "[{chain_id}] {subject}: {message}";
+
+/// Default title template for Slack, which uses its own Markdown dialect.
+const DEFAULT_SLACK_TITLE: &str = "*[{chain_id}] {subject}*: {message}";
+
+/// Default body template for Datadog, rendered as Markdown.
+const DEFAULT_DATADOG_BODY: &str =
+    "**{severity}** `{kind}` alarm: {subject} {message}\n\n{details}";
+
+/// Default body template for PagerDuty, shown in the incident's custom details.
+const DEFAULT_PAGERDUTY_BODY: &str = "{details}";
+
+/// Destination alarms are sent to.
+#[derive(Debug)]
+pub struct Sink {
+    /// Name routes refer to the sink by.
+    name: String,
+
+    /// Where alarms are sent.
+    target: Target,
+
+    /// Maximum rate at which notifications are sent.
+    rate_limit: Option<RateLimitConfig>,
+
+    /// Template for each alarm's title.
+    title: String,
+
+    /// Template for each alarm's body.
+    body: String,
+}
+
+#[derive(Debug)]
+enum Target {
+    Datadog { dd_api_key: String },
+    Slack { webhook_url: String },
+    Pagerduty { routing_key: String },
+}
+
+impl Sink {
+    /// Create a sink from its configuration, using the `[datadog]` section's API key for
+    /// Datadog sinks which don't have their own.
+    pub fn new(config: &SinkConfig, datadog: Option<&DataDogConfig>) -> Result<Self, Error> {
+        let required = |value: &Option<String>, field: &str| {
+            value.clone().ok_or_else(|| {
+                Error::from(
+                    ErrorKind::Config
+                        .context(format!("sink '{}' has no {field} configured", config.name)),
+                )
+            })
+        };
+
+        let target = match config.sink_type {
+            SinkType::Datadog => Target::Datadog {
+                dd_api_key: required(
+                    &config
+                        .dd_api_key
+                        .clone()
+                        .or_else(|| datadog.and_then(|datadog| datadog.dd_api_key.clone())),
+                    "dd_api_key",
+                )?,
+            },
+            SinkType::Slack => Target::Slack {
+                webhook_url: required(&config.webhook_url, "webhook_url")?,
+            },
+            SinkType::Pagerduty => Target::Pagerduty {
+                routing_key: required(&config.routing_key, "routing_key")?,
+            },
+        };
+
+        let (default_title, default_body) = match config.sink_type {
+            SinkType::Datadog => (DEFAULT_TITLE, DEFAULT_DATADOG_BODY),
+            SinkType::Slack => (DEFAULT_SLACK_TITLE, ""),
+            SinkType::Pagerduty => (DEFAULT_TITLE, DEFAULT_PAGERDUTY_BODY),
+        };
+
+        let title = config.title.as_deref().unwrap_or(default_title);
+        let body = config.body.as_deref().unwrap_or(default_body);
+
+        for template in [title, body] {
+            template::check(template).map_err(|err| {
+                Error::from(
+                    ErrorKind::Config.context(format!("sink '{}' template: {err}", config.name)),
+                )
+            })?;
+        }
+
+        Ok(Self {
+            name: config.name.clone(),
+            target,
+            rate_limit: config.rate_limit.clone(),
+            title: title.to_owned(),
+            body: body.to_owned(),
+        })
+    }
+
+    /// Create the default Datadog sink used when no sinks are configured.
+    pub fn datadog(dd_api_key: String) -> Self {
+        Self {
+            name: "datadog".to_owned(),
+            target: Target::Datadog { dd_api_key },
+            rate_limit: None,
+            title: DEFAULT_TITLE.to_owned(),
+            body: DEFAULT_DATADOG_BODY.to_owned(),
+        }
+    }
+
+    /// Get the name of the sink.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the maximum rate at which notifications are sent to the sink, if it's limited.
+    pub fn rate_limit(&self) -> Option<&RateLimitConfig> {
+        self.rate_limit.as_ref()
+    }
+
+    /// Send a digest of alarms observed by the given instance to the sink.
+    ///
+    /// Only PagerDuty is told about resolved alarms, to close their incidents.
+    pub async fn send(&self, digest: &Digest, instance: &str, links: &Links) -> Result<(), Error> {
+        let resolved_only = digest.alarms.is_empty() && digest.suppressed.is_empty();
+
+        if resolved_only && !matches!(self.target, Target::Pagerduty { .. }) {
+            return Ok(());
+        }
+
+        let messages = digest
+            .alarms
+            .iter()
+            .map(|alarm| {
+                let context = Context::new(alarm, instance, links);
+
+                Message {
+                    severity: alarm.severity,
+                    title: context.render(&self.title),
+                    body: context.render(&self.body),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match &self.target {
+            Target::Datadog { dd_api_key } => {
+                send_datadog(digest, &messages, instance, dd_api_key.clone()).await
+            }
+            Target::Slack { webhook_url } => {
+                send_slack(digest, &messages, instance, webhook_url).await
+            }
+            Target::Pagerduty { routing_key } => {
+                send_pagerduty(digest, &messages, instance, routing_key).await
+            }
+        }
+    }
+}
+
+/// Alarm rendered with a sink's templates.
+struct Message {
+    severity: Severity,
+    title: String,
+    body: String,
+}
+
+/// Get the title of a notification: the title of its alarm if there's only one, otherwise a
+/// summary of the digest.
+fn notification_title(digest: &Digest, messages: &[Message]) -> String {
+    match messages {
+        [message] => message.title.clone(),
+        _ => digest.title(),
+    }
+}
+
+/// Single notification sent to a sink: the alarms grouped into it, along with the alarms
+/// suppressed by the sink's rate limit since its last notification.
+#[derive(Clone, Debug, Default)]
+pub struct Digest {
+    /// Alarms to report.
+    pub alarms: Vec<PagerAlarm>,
+
+    /// Alarms suppressed, counted once however many times they were re-sent.
+    pub suppressed: Set<AlarmKey>,
+
+    /// Reported alarms which have since resolved.
+    pub resolved: Vec<PagerAlarm>,
+}
+
+impl Digest {
+    /// Get the severity of the most severe alarm.
+    pub fn severity(&self) -> Severity {
+        self.alarms
+            .iter()
+            .map(|alarm| alarm.severity)
+            .max()
+            .unwrap_or(Severity::Warning)
+    }
+
+    /// Get a one-line summary of the digest.
+    pub fn title(&self) -> String {
+        match self.alarms.as_slice() {
+            [] => self.suppressed_summary().unwrap_or_default(),
+            [alarm] => alarm.to_string(),
+            alarms => {
+                let chains = alarms
+                    .iter()
+                    .map(|alarm| alarm.chain_id.as_str())
+                    .collect::<Set<_>>();
+
+                format!(
+                    "{} alarms firing on {}",
+                    alarms.len(),
+                    chains.into_iter().collect::<Vec<_>>().join(", ")
+                )
+            }
+        }
+    }
+
+    /// Get the number of alarms suppressed on each chain.
+    pub fn suppressed_by_chain(&self) -> Map<&chain::Id, usize> {
+        let mut counts = Map::new();
+
+        for (chain_id, _, _) in &self.suppressed {
+            *counts.entry(chain_id).or_default() += 1;
+        }
+
+        counts
+    }
+
+    /// Describe the alarms which were suppressed, if there were any.
+    pub fn suppressed_summary(&self) -> Option<String> {
+        let total = self.suppressed.len();
+
+        (total > 0).then(|| {
+            let chains = self
+                .suppressed_by_chain()
+                .iter()
+                .map(|(chain_id, count)| format!("{chain_id}: {count}"))
+                .collect::<Vec<_>>();
+
+            format!(
+                "{total} alarm(s) suppressed by rate limit ({})",
+                chains.join(", ")
+            )
+        })
+    }
+}
+
+/// Send a digest as a Datadog event with a Markdown body, which forwards critical alarms to
+/// PagerDuty.
+async fn send_datadog(
+    digest: &Digest,
+    messages: &[Message],
+    instance: &str,
+    dd_api_key: String,
+) -> Result<(), Error> {
+    let hostname = hostname::get().unwrap();
+    let mut ddtags = Map::new();
+    ddtags.insert("env".to_owned(), "staging".to_owned());
+    ddtags.insert("instance".to_owned(), instance.to_owned());
+    let sections = messages
+        .iter()
+        .map(|message| match messages {
+            [_] => message.body.clone(),
+            _ => format!("### {}\n{}", message.title, message.body),
+        })
+        .chain(digest.suppressed_summary())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let (alert_type, text) = match digest.severity() {
+        // Text field must contain @pagerduty to trigger alert
+        Severity::Critical => (
+            datadog::AlertType::Error,
+            format!("%%% \n@pagerduty\n\n{sections}\n %%%"),
+        ),
+        Severity::Warning => (
+            datadog::AlertType::Warning,
+            format!("%%% \n{sections}\n %%%"),
+        ),
+    };
+    let stream_event = StreamEvent {
+        aggregation_key: None,
+        alert_type: Some(alert_type),
+        date_happened: Some(SystemTime::now()),
+        device_name: None,
+        hostname: Some(hostname.to_string_lossy().to_string()),
+        priority: Some(datadog::Priority::Normal),
+        related_event_id: None,
+        tags: Some(ddtags),
+        text,
+        title: notification_title(digest, messages),
+    };
+
+    send_stream_event(&stream_event, dd_api_key)
+        .await
+        .map_err(|err| {
+            ErrorKind::Http
+                .context(format!("Datadog responded with status {}", err.code))
+                .into()
+        })
+}
+
+/// Send a digest to a Slack incoming webhook, starting each alarm on a new line.
+async fn send_slack(
+    digest: &Digest,
+    messages: &[Message],
+    instance: &str,
+    webhook_url: &str,
+) -> Result<(), Error> {
+    let mut lines = messages
+        .iter()
+        .map(|message| {
+            let emoji = match message.severity {
+                Severity::Critical => ":rotating_light:",
+                Severity::Warning => ":warning:",
+            };
+
+            if message.body.is_empty() {
+                format!("{emoji} {}", message.title)
+            } else {
+                format!("{emoji} {}\n{}", message.title, message.body)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(summary) = digest.suppressed_summary() {
+        lines.push(format!(":mute: {summary}"));
+    }
+
+    lines.push(format!("_(observed by {instance})_"));
+    let body = json!({ "text": lines.join("\n") });
+
+    http::request(Method::POST, webhook_url, &[], Some(body.to_string()))
+        .await
+        .map(drop)
+}
+
+/// Trigger a PagerDuty incident for each alarm in a digest and resolve the incidents of
+/// resolved alarms.
+async fn send_pagerduty(
+    digest: &Digest,
+    messages: &[Message],
+    instance: &str,
+    routing_key: &str,
+) -> Result<(), Error> {
+    for (url, event) in pagerduty_events(digest, messages, instance, routing_key) {
+        http::request(Method::POST, url, &[], Some(event.to_string())).await?;
+    }
+
+    Ok(())
+}
+
+/// Build the PagerDuty events for a digest, along with the URL each is sent to.
+///
+/// Each alarm triggers an incident deduplicated by chain, alarm kind and subject, so a re-sent
+/// alarm updates its existing incident and its resolution closes it. Alarms suppressed by the
+/// rate limit are summarized in a change event, which doesn't open an incident.
+fn pagerduty_events(
+    digest: &Digest,
+    messages: &[Message],
+    instance: &str,
+    routing_key: &str,
+) -> Vec<(&'static str, serde_json::Value)> {
+    let mut events = digest
+        .alarms
+        .iter()
+        .zip(messages)
+        .map(|(alarm, message)| {
+            let event = json!({
+                "routing_key": routing_key,
+                "event_action": "trigger",
+                "dedup_key": pagerduty_dedup_key(alarm),
+                "payload": {
+                    "summary": message.title,
+                    "source": instance,
+                    "severity": alarm.severity,
+                    "component": alarm.chain_id.as_str(),
+                    "class": alarm.kind.as_str(),
+                    "custom_details": {
+                        "details": message.body,
+                        "suppressed": digest.suppressed_by_chain(),
+                    },
+                },
+            });
+
+            (PAGERDUTY_EVENTS_URL, event)
+        })
+        .collect::<Vec<_>>();
+
+    if digest.alarms.is_empty()
+        && let Some(summary) = digest.suppressed_summary()
+    {
+        let event = json!({
+            "routing_key": routing_key,
+            "payload": {
+                "summary": summary,
+                "source": instance,
+                "custom_details": {
+                    "suppressed": digest.suppressed_by_chain(),
+                },
+            },
+        });
+
+        events.push((PAGERDUTY_CHANGE_EVENTS_URL, event));
+    }
+
+    for alarm in &digest.resolved {
+        let event = json!({
+            "routing_key": routing_key,
+            "event_action": "resolve",
+            "dedup_key": pagerduty_dedup_key(alarm),
+        });
+
+        events.push((PAGERDUTY_EVENTS_URL, event));
+    }
+
+    events
+}
+
+/// Get the key PagerDuty deduplicates an alarm's incident by.
+fn pagerduty_dedup_key(alarm: &PagerAlarm) -> String {
+    format!(
+        "{}/{}/{}",
+        alarm.chain_id,
+        alarm.kind.as_str(),
+        alarm.subject
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pagerduty_events, Digest, Message};
+    use crate::pager::{AlarmDetails, AlarmKind, PagerAlarm, Severity};
+    use tendermint::chain;
+
+    fn alarm(subject: &str) -> PagerAlarm {
+        PagerAlarm {
+            chain_id: chain::Id::try_from("test-1").unwrap(),
+            kind: AlarmKind::MissedBlocks,
+            severity: Severity::Critical,
+            subject: subject.to_owned(),
+            message: "missed 60 of the last 100 blocks".to_owned(),
+            details: AlarmDetails::default(),
+        }
+    }
+
+    /// Build the PagerDuty events for a digest, as (action, dedup key) pairs.
+    fn events(digest: Digest) -> Vec<(String, String)> {
+        let messages = digest
+            .alarms
+            .iter()
+            .map(|alarm| Message {
+                severity: alarm.severity,
+                title: alarm.to_string(),
+                body: String::new(),
+            })
+            .collect::<Vec<_>>();
+
+        pagerduty_events(&digest, &messages, "us-east", "key")
+            .into_iter()
+            .map(|(url, event)| {
+                let action = event["event_action"].as_str().unwrap_or("change");
+                let dedup_key = event["dedup_key"].as_str().unwrap_or_default();
+                assert_eq!(url.ends_with("/change/enqueue"), action == "change");
+                (action.to_owned(), dedup_key.to_owned())
+            })
+            .collect()
+    }
+
+    fn event(action: &str, dedup_key: &str) -> (String, String) {
+        (action.to_owned(), dedup_key.to_owned())
+    }
+
+    #[test]
+    fn pagerduty_events_reuse_dedup_keys() {
+        let first = events(Digest {
+            alarms: vec![alarm("main")],
+            ..Digest::default()
+        });
+        assert_eq!(first, [event("trigger", "test-1/missed_blocks/main")]);
+
+        // Re-sending the alarm alongside another updates its existing incident
+        let second = events(Digest {
+            alarms: vec![alarm("backup"), alarm("main")],
+            ..Digest::default()
+        });
+        assert_eq!(
+            second,
+            [
+                event("trigger", "test-1/missed_blocks/backup"),
+                event("trigger", "test-1/missed_blocks/main")
+            ]
+        );
+
+        // Its resolution closes the same incident
+        let resolved = events(Digest {
+            resolved: vec![alarm("main")],
+            ..Digest::default()
+        });
+        assert_eq!(resolved, [event("resolve", "test-1/missed_blocks/main")]);
+    }
+
+    #[test]
+    fn pagerduty_summarizes_suppressed_alarms_as_a_change() {
+        let digest = Digest {
+            suppressed: [alarm("main").key(), alarm("backup").key()].into(),
+            ..Digest::default()
+        };
+
+        assert_eq!(events(digest), [event("change", "")]);
+    }
+}
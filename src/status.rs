@@ -0,0 +1,295 @@
+//! Status service which tracks the latest observed state of each chain monitor.
+
+use crate::{
+    chain_state::Vote,
+    client_manager::EndpointStatus,
+    config::HealthConfig,
+    consensus_monitor::ConsensusStatus,
+    health::{HealthReport, Heartbeat},
+    node_monitor::NodeStatus,
+    signing_report::SigningSnapshot,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap as Map,
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tendermint::{account, block, chain, Hash, Time};
+use tower::Service;
+
+/// Status service.
+#[derive(Debug)]
+pub struct StatusService {
+    /// Chains which are expected to be monitored.
+    chain_ids: Vec<chain::Id>,
+
+    /// Latest status reported by each chain monitor.
+    chains: Map<chain::Id, ChainStatus>,
+
+    /// Latest consensus state reported by each consensus monitor.
+    consensus: Map<chain::Id, ConsensusStatus>,
+
+    /// Latest status reported by each node monitor, by node name.
+    nodes: Map<String, NodeStatus>,
+
+    /// Heartbeats registered by each chain monitor.
+    heartbeats: Map<chain::Id, Heartbeat>,
+
+    /// Health check configuration.
+    health_config: HealthConfig,
+}
+
+/// StatusFuture future returned from the service
+pub type StatusFuture =
+    Pin<Box<dyn Future<Output = Result<StatusResponse, Infallible>> + Send + 'static>>;
+
+/// StatusBuffer
+pub type StatusBuffer = tower::buffer::Buffer<StatusRequest, StatusFuture>;
+
+impl StatusService {
+    /// Create a new status service for the given chains.
+    pub fn new(chain_ids: Vec<chain::Id>, health_config: HealthConfig) -> Self {
+        Self {
+            chain_ids,
+            chains: Map::default(),
+            consensus: Map::default(),
+            nodes: Map::default(),
+            heartbeats: Map::default(),
+            health_config,
+        }
+    }
+
+    /// Get the best height known for the given chain, from its chain monitor or any of our nodes.
+    fn best_height(&self, chain_id: &chain::Id) -> Option<block::Height> {
+        self.chains
+            .get(chain_id)
+            .map(|chain| chain.height)
+            .into_iter()
+            .chain(
+                self.nodes
+                    .values()
+                    .filter(|node| &node.chain_id == chain_id)
+                    .filter_map(|node| node.height),
+            )
+            .max()
+    }
+
+    fn health(&self) -> HealthReport {
+        HealthReport::new(
+            &self.health_config,
+            self.chain_ids.iter().map(|chain_id| {
+                (
+                    chain_id,
+                    self.heartbeats.get(chain_id),
+                    self.chains.get(chain_id),
+                )
+            }),
+        )
+    }
+}
+
+impl Service<StatusRequest> for StatusService {
+    type Response = StatusResponse;
+    type Error = Infallible;
+    type Future = StatusFuture;
+
+    fn poll_ready(&mut self, _ctx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: StatusRequest) -> Self::Future {
+        let response = match request {
+            StatusRequest::Update(mut status) => {
+                status.consensus = self.consensus.get(&status.chain_id).cloned();
+                self.chains.insert(status.chain_id.clone(), status);
+                StatusResponse::Updated
+            }
+            StatusRequest::UpdateConsensus(consensus) => {
+                if let Some(chain) = self.chains.get_mut(&consensus.chain_id) {
+                    chain.consensus = Some(consensus.clone());
+                }
+
+                self.consensus.insert(consensus.chain_id.clone(), consensus);
+                StatusResponse::Updated
+            }
+            StatusRequest::GetChains => {
+                StatusResponse::Chains(self.chains.values().cloned().collect())
+            }
+            StatusRequest::GetChain(chain_id) => {
+                StatusResponse::Chain(self.chains.get(&chain_id).cloned().map(Box::new))
+            }
+            StatusRequest::UpdateNode(status) => {
+                self.nodes.insert(status.name.clone(), status);
+                StatusResponse::Updated
+            }
+            StatusRequest::GetNodes => {
+                StatusResponse::Nodes(self.nodes.values().cloned().collect())
+            }
+            StatusRequest::BestHeight(chain_id) => {
+                StatusResponse::BestHeight(self.best_height(&chain_id))
+            }
+            StatusRequest::Register {
+                chain_id,
+                heartbeat,
+            } => {
+                self.heartbeats.insert(chain_id, heartbeat);
+                StatusResponse::Updated
+            }
+            StatusRequest::GetHealth => StatusResponse::Health(self.health()),
+        };
+        Box::pin(async { Ok(response) })
+    }
+}
+
+/// Requests sent to the status service.
+#[derive(Debug)]
+pub enum StatusRequest {
+    /// Replace the status of a chain with a newer one.
+    Update(ChainStatus),
+
+    /// Get the status of all chains.
+    GetChains,
+
+    /// Get the status of a particular chain.
+    GetChain(chain::Id),
+
+    /// Replace the consensus state of a chain with a newer one.
+    UpdateConsensus(ConsensusStatus),
+
+    /// Replace the status of one of our nodes with a newer one.
+    UpdateNode(NodeStatus),
+
+    /// Get the status of all of our nodes.
+    GetNodes,
+
+    /// Get the best height known for a particular chain.
+    BestHeight(chain::Id),
+
+    /// Register the heartbeat of a chain monitor.
+    Register {
+        /// Chain ID being monitored.
+        chain_id: chain::Id,
+
+        /// Heartbeat updated by the monitor.
+        heartbeat: Heartbeat,
+    },
+
+    /// Get the health of all chain monitors.
+    GetHealth,
+}
+
+/// Responses sent from the status service.
+#[derive(Debug)]
+pub enum StatusResponse {
+    /// Update responses contain no data.
+    Updated,
+
+    /// Status of all known chains.
+    Chains(Vec<ChainStatus>),
+
+    /// Status of a particular chain, if known.
+    Chain(Option<Box<ChainStatus>>),
+
+    /// Status of all of our nodes.
+    Nodes(Vec<NodeStatus>),
+
+    /// Best height known for a particular chain, if any.
+    BestHeight(Option<block::Height>),
+
+    /// Health of all chain monitors.
+    Health(HealthReport),
+}
+
+/// Snapshot of a chain monitor's state.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChainStatus {
+    /// Chain ID.
+    pub chain_id: chain::Id,
+
+    /// Latest imported block height.
+    pub height: block::Height,
+
+    /// Estimated consensus time in milliseconds.
+    pub consensus_time_ms: u64,
+
+    /// Signing status of each validator being monitored.
+    pub validators: Vec<ValidatorStatus>,
+
+    /// Health of each RPC endpoint.
+    pub rpc_endpoints: Vec<EndpointStatus>,
+
+    /// Time the latest block was imported.
+    pub imported_at: Time,
+
+    /// Time this snapshot was taken.
+    pub updated_at: Time,
+
+    /// Recent blocks, starting with the most recent.
+    #[serde(skip)]
+    pub blocks: Vec<BlockStatus>,
+
+    /// Data signing stats for the whole validator set are computed from, if it's known.
+    #[serde(skip)]
+    pub signing: Option<SigningSnapshot>,
+
+    /// Latest consensus state, if it's known.
+    pub consensus: Option<ConsensusStatus>,
+}
+
+/// Signing status of a validator over the history window.
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorStatus {
+    /// Name of the validator.
+    pub name: String,
+
+    /// Validator whose signatures are being monitored.
+    pub addr: account::Id,
+
+    /// Number of blocks in the history window which are missing this validator's signature.
+    pub missed_blocks: usize,
+
+    /// Number of consecutive recent blocks containing this validator's signature.
+    pub recent_blocks: usize,
+
+    /// Number of blocks in the history window where this validator precommitted nil.
+    pub nil_votes: usize,
+
+    /// Percentage of blocks in the history window which contain this validator's signature.
+    pub uptime: Option<f64>,
+
+    /// Number of blocks in the history window proposed by this validator.
+    pub proposed_blocks: usize,
+
+    /// Number of rounds where this validator was scheduled to propose but the round advanced
+    /// without its block.
+    pub missed_proposals: usize,
+
+    /// Voting power in the current validator set, `0` if the validator isn't in the set, or
+    /// `None` if the validator set isn't known.
+    pub voting_power: Option<u64>,
+
+    /// Percentage of scheduled proposals which produced a block.
+    pub proposal_success_rate: Option<f64>,
+}
+
+/// Signing status of an individual block.
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockStatus {
+    /// Block height.
+    pub height: block::Height,
+
+    /// Block hash.
+    pub hash: Hash,
+
+    /// Block time.
+    pub time: Time,
+
+    /// Vote cast by each validator in this block's last commit.
+    ///
+    /// Listed in the same order as [`ChainStatus::validators`], with `None` if the
+    /// block doesn't have a last commit.
+    pub votes: Vec<Option<Vote>>,
+}
@@ -0,0 +1,267 @@
+//! Alarm message templates.
+//!
+//! Templates are plain strings with `{placeholder}`s which are replaced with details of the alarm,
+//! e.g. `"[{chain_id}] {subject}: {message}"`. Use `{{` and `}}` for literal braces. Placeholders
+//! whose value isn't known for a particular alarm (e.g. `{missed_blocks}` for a chain halt) are
+//! replaced with nothing.
+
+use crate::{config::ObservatoryConfig, pager::PagerAlarm};
+use std::collections::BTreeMap as Map;
+use tendermint::chain;
+
+/// Placeholders which may be used in templates.
+pub const PLACEHOLDERS: &[&str] = &[
+    "chain_id",
+    "kind",
+    "severity",
+    "subject",
+    "message",
+    "instance",
+    "moniker",
+    "height",
+    "missed_blocks",
+    "window_blocks",
+    "uptime",
+    "explorer_url",
+    "runbook_url",
+    "details",
+];
+
+/// Links included in alarms, which are themselves templates.
+#[derive(Clone, Debug, Default)]
+pub struct Links {
+    /// Block explorer URL of each chain.
+    explorer_urls: Map<chain::Id, String>,
+
+    /// Runbook URL.
+    runbook_url: Option<String>,
+}
+
+impl Links {
+    /// Get the links configured for each chain and in `[alarms]`.
+    pub fn new(config: &ObservatoryConfig) -> Self {
+        Self {
+            explorer_urls: config
+                .chains
+                .iter()
+                .filter_map(|chain| Some((chain.id.clone(), chain.explorer_url.clone()?)))
+                .collect(),
+            runbook_url: config.alarms.runbook_url.clone(),
+        }
+    }
+}
+
+/// Values of each placeholder for a particular alarm.
+#[derive(Clone, Debug)]
+pub struct Context {
+    values: Map<&'static str, String>,
+}
+
+impl Context {
+    /// Get the placeholder values for an alarm observed by the given instance.
+    pub fn new(alarm: &PagerAlarm, instance: &str, links: &Links) -> Self {
+        let details = &alarm.details;
+        let mut values = Map::new();
+        values.insert("chain_id", alarm.chain_id.to_string());
+        values.insert("kind", alarm.kind.as_str().to_owned());
+        values.insert("severity", alarm.severity.as_str().to_owned());
+        values.insert("subject", alarm.subject.clone());
+        values.insert("message", alarm.message.clone());
+        values.insert("instance", instance.to_owned());
+
+        if let Some(moniker) = &details.moniker {
+            values.insert("moniker", moniker.clone());
+        }
+
+        if let Some(height) = details.height {
+            values.insert("height", height.to_string());
+        }
+
+        if let Some(missed_blocks) = details.missed_blocks {
+            values.insert("missed_blocks", missed_blocks.to_string());
+        }
+
+        if let Some(window_blocks) = details.window_blocks {
+            values.insert("window_blocks", window_blocks.to_string());
+        }
+
+        if let Some(uptime) = details.uptime() {
+            values.insert("uptime", format!("{uptime:.2}"));
+        }
+
+        // Links may use any of the values above
+        let links = [
+            ("explorer_url", links.explorer_urls.get(&alarm.chain_id)),
+            ("runbook_url", links.runbook_url.as_ref()),
+        ];
+
+        for (placeholder, template) in links {
+            if let Some(template) = template {
+                let url = render(template, &values);
+                values.insert(placeholder, url);
+            }
+        }
+
+        values.insert("details", describe(&values));
+        Self { values }
+    }
+
+    /// Render a template.
+    pub fn render(&self, template: &str) -> String {
+        render(template, &self.values)
+    }
+}
+
+/// Check a template only uses known placeholders, returning the first unknown one.
+pub fn check(template: &str) -> Result<(), String> {
+    let mut unknown = None;
+
+    substitute(template, |placeholder| {
+        if !PLACEHOLDERS.contains(&placeholder) && unknown.is_none() {
+            unknown = Some(placeholder.to_owned());
+        }
+
+        None
+    });
+
+    match unknown {
+        Some(placeholder) => Err(format!("unknown placeholder {{{placeholder}}}")),
+        None => Ok(()),
+    }
+}
+
+/// Render a template with the given values.
+fn render(template: &str, values: &Map<&'static str, String>) -> String {
+    substitute(template, |placeholder| {
+        PLACEHOLDERS
+            .contains(&placeholder)
+            .then(|| values.get(placeholder).cloned().unwrap_or_default())
+    })
+}
+
+/// Replace each `{placeholder}` in a template using `value`, leaving placeholders it returns
+/// `None` for as they are.
+fn substitute(template: &str, mut value: impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(index) = rest.find(['{', '}']) {
+        output.push_str(&rest[..index]);
+        let brace = &rest[index..index + 1];
+        rest = &rest[index + 1..];
+
+        if rest.starts_with(brace) {
+            output.push_str(brace);
+            rest = &rest[1..];
+            continue;
+        }
+
+        match rest.find('}') {
+            Some(end) if brace == "{" => {
+                let placeholder = &rest[..end];
+
+                match value(placeholder) {
+                    Some(value) => output.push_str(&value),
+                    None => {
+                        output.push('{');
+                        output.push_str(placeholder);
+                        output.push('}');
+                    }
+                }
+
+                rest = &rest[end + 1..];
+            }
+            _ => output.push_str(brace),
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Describe the known details of an alarm as a Markdown list.
+fn describe(values: &Map<&'static str, String>) -> String {
+    let mut lines = Vec::new();
+
+    if let Some(moniker) = values.get("moniker") {
+        lines.push(format!("- Moniker: {moniker}"));
+    }
+
+    if let Some(height) = values.get("height") {
+        lines.push(format!("- Height: {height}"));
+    }
+
+    if let (Some(missed), Some(window)) = (values.get("missed_blocks"), values.get("window_blocks"))
+    {
+        let uptime = values.get("uptime").map(String::as_str).unwrap_or("?");
+        lines.push(format!(
+            "- Missed blocks: {missed} of {window} ({uptime}% uptime)"
+        ));
+    }
+
+    if let Some(url) = values.get("explorer_url") {
+        lines.push(format!("- Explorer: {url}"));
+    }
+
+    if let Some(url) = values.get("runbook_url") {
+        lines.push(format!("- Runbook: {url}"));
+    }
+
+    lines.push(format!("- Observed by: {}", values["instance"]));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, Context, Links};
+    use crate::pager::{AlarmDetails, AlarmKind, PagerAlarm, Severity};
+    use tendermint::{block, chain};
+
+    #[test]
+    fn renders_alarm_details() {
+        let chain_id = chain::Id::try_from("cosmoshub-4").unwrap();
+        let alarm = PagerAlarm {
+            chain_id: chain_id.clone(),
+            kind: AlarmKind::MissedBlocks,
+            severity: Severity::Critical,
+            subject: "main".to_owned(),
+            message: "missed 50 blocks!".to_owned(),
+            details: AlarmDetails {
+                moniker: Some("Our Validator".to_owned()),
+                height: Some(block::Height::from(1000_u32)),
+                missed_blocks: Some(50),
+                window_blocks: Some(200),
+            },
+        };
+        let links = Links {
+            explorer_urls: [(
+                chain_id,
+                "https://example.com/{chain_id}/{height}".to_owned(),
+            )]
+            .into_iter()
+            .collect(),
+            runbook_url: Some("https://wiki.example.com/runbooks#{kind}".to_owned()),
+        };
+
+        let context = Context::new(&alarm, "us-east", &links);
+        assert_eq!(
+            context.render("{{{severity}}} [{chain_id}] {moniker}: {uptime}% {unknown} {"),
+            "{critical} [cosmoshub-4] Our Validator: 75.00% {unknown} {"
+        );
+        assert_eq!(
+            context.render("{details}"),
+            "- Moniker: Our Validator\n\
+             - Height: 1000\n\
+             - Missed blocks: 50 of 200 (75.00% uptime)\n\
+             - Explorer: https://example.com/cosmoshub-4/1000\n\
+             - Runbook: https://wiki.example.com/runbooks#missed_blocks\n\
+             - Observed by: us-east"
+        );
+
+        assert!(check("[{chain_id}] {subject}: {message}").is_ok());
+        assert_eq!(
+            check("{chain} {{literal}}").unwrap_err(),
+            "unknown placeholder {chain}"
+        );
+    }
+}
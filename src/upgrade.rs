@@ -0,0 +1,124 @@
+//! Planned chain upgrades, which halt the chain at a known height.
+//!
+//! Plans are either configured per chain or discovered by querying the Cosmos SDK upgrade
+//! module's current plan via `abci_query`.
+
+use crate::client_manager::ClientManager;
+use prost::Message;
+use serde::Serialize;
+use std::fmt;
+use tendermint::block;
+use tendermint_rpc::Client;
+
+/// ABCI query path for the upgrade module's current plan.
+const CURRENT_PLAN_PATH: &str = "/cosmos.upgrade.v1beta1.Query/CurrentPlan";
+
+/// Planned upgrade.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct UpgradePlan {
+    /// Name of the upgrade, if known.
+    pub name: Option<String>,
+
+    /// Height at which the chain halts for the upgrade.
+    pub height: block::Height,
+}
+
+impl UpgradePlan {
+    /// Is the given latest block height within `window` blocks of the upgrade height on either
+    /// side? The chain halts after committing the block before the upgrade height, so that
+    /// height is always covered.
+    pub fn covers(&self, height: block::Height, window: u64) -> bool {
+        let upgrade_height = self.height.value();
+        let height = height.value();
+
+        height + window.max(1) >= upgrade_height && height < upgrade_height + window
+    }
+}
+
+impl fmt::Display for UpgradePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "upgrade '{name}' at height {}", self.height),
+            None => write!(f, "upgrade at height {}", self.height),
+        }
+    }
+}
+
+/// Query the upgrade module's current plan through any of the given RPC endpoints.
+pub async fn current_plan(client_manager: &ClientManager) -> Option<UpgradePlan> {
+    client_manager
+        .request(|client| {
+            client.abci_query(Some(CURRENT_PLAN_PATH.to_owned()), vec![], None, false)
+        })
+        .await
+        .into_iter()
+        .filter_map(|result| result.ok())
+        .find(|response| response.code.is_ok())
+        .and_then(|response| QueryCurrentPlanResponse::decode(response.value.as_slice()).ok())
+        .and_then(|response| response.plan)
+        .and_then(|plan| {
+            let height = block::Height::try_from(plan.height).ok()?;
+
+            (height.value() > 0).then(|| UpgradePlan {
+                name: Some(plan.name).filter(|name| !name.is_empty()),
+                height,
+            })
+        })
+}
+
+/// `cosmos.upgrade.v1beta1.QueryCurrentPlanResponse`
+#[derive(Clone, PartialEq, Message)]
+struct QueryCurrentPlanResponse {
+    #[prost(message, optional, tag = "1")]
+    plan: Option<Plan>,
+}
+
+/// `cosmos.upgrade.v1beta1.Plan` (only the fields we need)
+#[derive(Clone, PartialEq, Message)]
+struct Plan {
+    #[prost(string, tag = "1")]
+    name: String,
+
+    #[prost(int64, tag = "3")]
+    height: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Plan, QueryCurrentPlanResponse, UpgradePlan};
+    use prost::Message;
+    use tendermint::block;
+
+    #[test]
+    fn upgrade_window() {
+        let plan = UpgradePlan {
+            name: Some("v2".to_owned()),
+            height: block::Height::from(100u32),
+        };
+        let covers = |height: u32, window: u64| plan.covers(block::Height::from(height), window);
+
+        assert!(!covers(89, 10));
+        assert!(covers(90, 10));
+        assert!(covers(109, 10));
+        assert!(!covers(110, 10));
+        assert!(covers(99, 0));
+        assert!(!covers(100, 0));
+    }
+
+    #[test]
+    fn decodes_current_plan() {
+        // plan { name: "v2", height: 100, info: "x" }
+        let bytes = [
+            0x0a, 0x09, 0x0a, 0x02, b'v', b'2', 0x18, 0x64, 0x22, 0x01, b'x',
+        ];
+
+        let response = QueryCurrentPlanResponse::decode(bytes.as_slice()).unwrap();
+        assert_eq!(
+            response.plan,
+            Some(Plan {
+                name: "v2".to_owned(),
+                height: 100,
+            })
+        );
+    }
+}